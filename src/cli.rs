@@ -0,0 +1,334 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use color_eyre::eyre::{Context, OptionExt, Result, bail};
+use ratatui::{
+    backend::IntoCrossterm,
+    buffer::Buffer,
+    crossterm::{
+        queue,
+        style::{Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    },
+    layout::Rect,
+    widgets::Widget,
+};
+
+use crate::{
+    game::{
+        logic::{
+            generator::{Difficulty, Generator},
+            grid::{Grid, LevelFormat, MoveDir},
+            solver::{Solution, Solver},
+        },
+        ui::grid_widget::GridWidget,
+    },
+    screens::{Screen, game::GameScreen, menu::MenuScreen},
+    storage::session::Session,
+};
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "A sliding color tile puzzle",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    /// Level file to open straight into the game
+    path: Option<PathBuf>,
+
+    /// Restore the last unfinished game
+    #[arg(long, conflicts_with = "path")]
+    resume: bool,
+
+    /// Play a level from a share code
+    #[arg(long, conflicts_with_all = ["path", "resume"])]
+    code: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check that level files load and report anything that makes them unwinnable
+    Validate {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Fail on warnings as well as errors
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Rewrite level files saved in an older format into the current one
+    Upgrade {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Only report outdated files, failing if there are any
+        #[arg(long)]
+        check: bool,
+    },
+    /// Copy a level into another format, picked from the output extension (.tiles for compact, otherwise RON)
+    Convert { input: PathBuf, output: PathBuf },
+    /// Print the share code of a level
+    Code { path: PathBuf },
+    /// Generate a random level that is guaranteed to be clearable
+    Generate {
+        /// Where to save the level, printed in the compact format when left out
+        output: Option<PathBuf>,
+        #[arg(long, default_value_t = 5)]
+        height: usize,
+        #[arg(long, default_value_t = 5)]
+        width: usize,
+        #[arg(long, default_value_t = 3)]
+        colors: usize,
+        /// Share of the board covered by blockers, from 0 to 1
+        #[arg(long, default_value_t = 0.1)]
+        blockers: f64,
+        #[arg(long, value_enum, default_value_t = DifficultyArg::Normal)]
+        difficulty: DifficultyArg,
+        /// Random when left out
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Print the shortest sequence of moves that clears a level
+    Solve {
+        path: PathBuf,
+        /// Give up after exploring this many boards
+        #[arg(long, default_value_t = 1_000_000)]
+        limit: usize,
+    },
+    /// Print a level to the terminal
+    Render {
+        path: PathBuf,
+        /// Tile size in terminal rows, from 2 to 16
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u16).range(2..=16))]
+        scale: u16,
+    },
+}
+
+// Difficulty as a command line value, so the generator itself doesn't depend on clap
+#[derive(Clone, Copy, ValueEnum)]
+enum DifficultyArg {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl From<DifficultyArg> for Difficulty {
+    fn from(difficulty: DifficultyArg) -> Self {
+        match difficulty {
+            DifficultyArg::Easy => Difficulty::Easy,
+            DifficultyArg::Normal => Difficulty::Normal,
+            DifficultyArg::Hard => Difficulty::Hard,
+        }
+    }
+}
+
+impl Cli {
+    // Returns None when a subcommand ran to completion and no terminal UI is needed
+    pub fn run(self) -> Result<Option<Vec<Box<dyn Screen>>>> {
+        if let Some(command) = self.command {
+            command.run()?;
+            return Ok(None);
+        }
+
+        let mut screens: Vec<Box<dyn Screen>> = vec![Box::new(MenuScreen::main_menu())];
+        if let Some(path) = self.path {
+            screens.push(Box::new(GameScreen::from_file(&path)?));
+        } else if let Some(code) = self.code {
+            let grid = Grid::from_share_code(&code).wrap_err("Invalid level code")?;
+            screens.push(Box::new(GameScreen::from_grid(grid)));
+        } else if self.resume {
+            let session = Session::load().ok_or_eyre("There is no unfinished game to resume")?;
+            screens.push(Box::new(GameScreen::from_session(session)));
+        }
+        Ok(Some(screens))
+    }
+}
+
+impl Command {
+    fn run(self) -> Result<()> {
+        match self {
+            Command::Validate { paths, strict } => Self::validate(&paths, strict),
+            Command::Upgrade { paths, check } => Self::upgrade(&paths, check),
+            Command::Convert { input, output } => {
+                let text = Self::rewrite(&Grid::from_file(&input)?, LevelFormat::from_path(&output))?;
+                fs::write(&output, text).wrap_err("Could not write file")
+            }
+            Command::Code { path } => {
+                println!("{}", Grid::from_file(&path)?.to_share_code()?);
+                Ok(())
+            }
+            Command::Generate {
+                output,
+                height,
+                width,
+                colors,
+                blockers,
+                difficulty,
+                seed,
+            } => {
+                let generator = Generator::new(height, width)
+                    .colors(colors)
+                    .blocker_density(blockers)
+                    .difficulty(difficulty.into())
+                    .seed(seed.unwrap_or_else(Generator::random_seed));
+                let level = generator
+                    .generate()
+                    .ok_or_eyre("No level found for these settings, try a larger board or fewer colors")?;
+                eprintln!("seed {}, solvable in {} moves", generator.get_seed(), level.par);
+                match output {
+                    Some(path) => fs::write(&path, level.grid.to_text(LevelFormat::from_path(&path))?)
+                        .wrap_err("Could not write file"),
+                    None => {
+                        print!("{}", level.grid.to_text(LevelFormat::Compact)?);
+                        Ok(())
+                    }
+                }
+            }
+            Command::Solve { path, limit } => Self::solve(&Grid::from_file(&path)?, limit),
+            Command::Render { path, scale } => Self::render(&Grid::from_file(&path)?, scale),
+        }
+    }
+
+    fn validate(paths: &[PathBuf], strict: bool) -> Result<()> {
+        let mut failed = 0;
+        for path in paths {
+            match Grid::from_file(path) {
+                Ok(grid) => {
+                    let warnings = grid.warnings();
+                    println!(
+                        "ok      {} ({}x{}, {} steps)",
+                        path.display(),
+                        grid.get_height(),
+                        grid.get_width(),
+                        grid.steps
+                    );
+                    for warning in &warnings {
+                        println!("warning {}: {warning}", path.display());
+                    }
+                    if strict && !warnings.is_empty() {
+                        failed += 1;
+                    }
+                }
+                Err(err) => {
+                    failed += 1;
+                    println!("error   {}: {err:#}", path.display());
+                }
+            }
+        }
+        if failed > 0 {
+            bail!("{failed} of {} level files failed validation", paths.len());
+        }
+        Ok(())
+    }
+
+    fn upgrade(paths: &[PathBuf], check: bool) -> Result<()> {
+        let mut failed = 0;
+        for path in paths {
+            match Self::upgrade_file(path, check) {
+                Ok(Some((version, _))) if check => {
+                    failed += 1;
+                    println!("outdated {} (version {version})", path.display());
+                }
+                Ok(Some((version, upgraded))) => {
+                    println!("upgraded {} (version {version} to {upgraded})", path.display())
+                }
+                Ok(None) => println!("current  {}", path.display()),
+                Err(err) => {
+                    failed += 1;
+                    println!("error    {}: {err:#}", path.display());
+                }
+            }
+        }
+        if failed > 0 {
+            bail!("{failed} of {} level files are not current", paths.len());
+        }
+        Ok(())
+    }
+
+    // Returns the version the file was saved with and the one it is rewritten in, or None if it is
+    // already current. Files stay as they are once their version can hold everything they use.
+    fn upgrade_file(path: &Path, check: bool) -> Result<Option<(u32, u32)>> {
+        let content = fs::read_to_string(path).wrap_err("Could not read file")?;
+        let format = LevelFormat::of_file(path, &content);
+        let version = Grid::stored_version(&content, format).wrap_err("Invalid level file")?;
+        let grid = Grid::parse(&content, format).wrap_err("Invalid level file")?;
+        let target = grid.format_version();
+        if version >= target {
+            return Ok(None);
+        }
+        let upgraded = Self::rewrite(&grid, format)?;
+        if !check {
+            fs::write(path, upgraded).wrap_err("Could not write file")?;
+        }
+        Ok(Some((version, target)))
+    }
+
+    // Reads the rewritten level back before anything touches the disk, so a lossy conversion is never saved
+    fn rewrite(grid: &Grid, format: LevelFormat) -> Result<String> {
+        let text = grid.to_text(format)?;
+        let reread = Grid::parse(&text, format).wrap_err("Rewritten level does not load")?;
+        if reread.steps != grid.steps || reread.fingerprint() != grid.fingerprint() {
+            bail!("Rewritten level does not match the original");
+        }
+        Ok(text)
+    }
+
+    fn solve(grid: &Grid, limit: usize) -> Result<()> {
+        match Solver::new().state_limit(limit).solve(grid) {
+            Solution::Solved(moves) => {
+                let arrows: Vec<&str> = moves
+                    .iter()
+                    .map(|direction| match direction {
+                        MoveDir::Up => "↑",
+                        MoveDir::Down => "↓",
+                        MoveDir::Left => "←",
+                        MoveDir::Right => "→",
+                    })
+                    .collect();
+                println!("{} moves: {}", moves.len(), arrows.join(" "));
+                Ok(())
+            }
+            Solution::Unsolvable { explored } => {
+                bail!("No solution within {} steps ({explored} boards explored)", grid.steps)
+            }
+            Solution::GaveUp { explored } => bail!("Gave up after exploring {explored} boards"),
+        }
+    }
+
+    fn render(grid: &Grid, scale: u16) -> Result<()> {
+        // GridWidget leaves a 2 column, 1 row margin and a gap after every tile. Tiles are twice as wide as
+        // they are tall.
+        let size = |tiles: usize, aspect: u16, margin: u16| {
+            u16::try_from(tiles)
+                .ok()
+                .and_then(|tiles| tiles.checked_mul(scale)?.checked_mul(aspect)?.checked_add(margin))
+        };
+        let (Some(width), Some(height)) = (size(grid.get_width(), 2, 2), size(grid.get_height(), 1, 1)) else {
+            bail!("Level is too large to render at scale {scale}");
+        };
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        GridWidget::new(grid).render(area, &mut buf);
+
+        let mut stdout = io::stdout().lock();
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = &buf[(x, y)];
+                queue!(
+                    stdout,
+                    SetForegroundColor(cell.fg.into_crossterm()),
+                    SetBackgroundColor(cell.bg.into_crossterm()),
+                    Print(cell.symbol()),
+                )?;
+            }
+            queue!(stdout, ResetColor, Print("\n"))?;
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+}
@@ -1 +1,7 @@
+pub mod generator;
 pub mod grid;
+pub mod history;
+pub mod level_pack;
+pub mod rng;
+pub mod score;
+pub mod solver;
@@ -0,0 +1,186 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+use crate::game::logic::{
+    grid::{
+        Grid,
+        rules::Rules,
+        tile::{Moves, PALETTE, Tile, TileKind},
+    },
+    rng::Rng,
+    solver::{Solution, Solver},
+};
+
+// Boards are only kept if the solver proves them clearable, so give up on a seed after this many tries
+const MAX_ATTEMPTS: usize = 500;
+const STATE_LIMIT: usize = 20_000;
+const TILES_PER_COLOR: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    // Length of the shortest solution a generated level must have
+    fn moves(self) -> (usize, usize) {
+        match self {
+            Difficulty::Easy => (1, 4),
+            Difficulty::Normal => (4, 8),
+            Difficulty::Hard => (7, usize::MAX),
+        }
+    }
+
+    // Spare steps on top of the shortest solution
+    fn slack(self) -> usize {
+        match self {
+            Difficulty::Easy => 4,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 0,
+        }
+    }
+}
+
+pub struct GeneratedLevel {
+    pub grid: Grid,
+    pub par: usize,
+}
+
+// Places tiles at random and keeps the first board the solver can clear with a fitting number of moves
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Generator {
+    height: usize,
+    width: usize,
+    colors: usize,
+    blocker_density: f64,
+    difficulty: Difficulty,
+    seed: u64,
+}
+
+impl Generator {
+    pub fn new(height: usize, width: usize) -> Self {
+        Self {
+            height: height.max(1),
+            width: width.max(1),
+            colors: 2,
+            blocker_density: 0.1,
+            difficulty: Difficulty::Normal,
+            seed: 0,
+        }
+    }
+
+    // Fixed settings so the day number alone decides the board, the same one for everybody
+    pub fn daily(day: u64) -> Self {
+        Self::new(5, 5).colors(3).difficulty(Difficulty::Normal).seed(day)
+    }
+
+    pub fn colors(mut self, colors: usize) -> Self {
+        self.colors = colors.clamp(1, PALETTE.len());
+        self
+    }
+
+    pub fn blocker_density(mut self, blocker_density: f64) -> Self {
+        self.blocker_density = blocker_density.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    // Seed from the clock, for when reproducing the level doesn't matter
+    pub fn random_seed() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    // e.g. "Normal 5x5 #1234", enough to tell generated levels apart and regenerate one
+    pub fn describe(&self) -> String {
+        let difficulty = match self.difficulty {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        };
+        format!("{difficulty} {}x{} #{}", self.height, self.width, self.seed)
+    }
+
+    // The same settings with the following seed, for a fresh level of the same kind
+    pub fn next_seed(&self) -> Self {
+        self.clone().seed(self.seed.wrapping_add(1))
+    }
+
+    // None if no fitting level turned up, usually because the board is too crowded or too small
+    pub fn generate(&self) -> Option<GeneratedLevel> {
+        self.generate_until(Arc::default())
+    }
+
+    // Like `generate`, but also gives up with None once `cancel` is set
+    pub fn generate_until(&self, cancel: Arc<AtomicBool>) -> Option<GeneratedLevel> {
+        let cells = self.height * self.width;
+        let regular = self.colors * TILES_PER_COLOR;
+        // Leave at least one empty cell so something can move
+        let blockers = ((cells as f64 * self.blocker_density) as usize).min(cells.checked_sub(regular + 1)?);
+
+        let (min_moves, max_moves) = self.difficulty.moves();
+        let solver = Solver::new().state_limit(STATE_LIMIT).cancel_flag(cancel.clone());
+        let mut rng = Rng::new(self.seed);
+
+        for _ in 0..MAX_ATTEMPTS {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let mut grid = self.random_board(&mut rng, blockers);
+            let Solution::Solved(moves) = solver.solve(&grid) else {
+                continue;
+            };
+            if (min_moves..=max_moves).contains(&moves.len()) {
+                grid.steps = moves.len() + self.difficulty.slack();
+                return Some(GeneratedLevel { grid, par: moves.len() });
+            }
+        }
+        None
+    }
+
+    fn random_board(&self, rng: &mut Rng, blockers: usize) -> Grid {
+        let cells = self.height * self.width;
+        let mut palette = PALETTE;
+        rng.shuffle(&mut palette);
+
+        let mut tiles: Vec<Tile> = palette[..self.colors]
+            .iter()
+            .flat_map(|&color| (0..TILES_PER_COLOR).map(move |_| TileKind::Regular(color, Moves::Any).to_tile()))
+            .collect();
+        tiles.extend((0..blockers).map(|_| Tile::Blocker));
+        tiles.resize(cells, Tile::Empty);
+        rng.shuffle(&mut tiles);
+
+        Grid {
+            // Plenty of steps for the solver, the real budget is set once the shortest solution is known
+            steps: cells * TILES_PER_COLOR,
+            tiles: Array2::from_shape_vec((self.height, self.width), tiles).unwrap(),
+            rules: Rules::default(),
+            walls: Vec::new(),
+        }
+    }
+}
@@ -1,31 +1,87 @@
-pub mod anim;
+mod compact;
+pub mod event;
+pub mod rules;
+mod share_code;
 pub mod tile;
+pub mod validation;
 mod vec_grid;
+pub mod wall;
 
-use std::time::Instant;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+};
 
-use color_eyre::eyre::{Ok, Result};
+use color_eyre::eyre::{Context, Ok, Result};
 use ndarray::prelude::*;
+use ratatui::style::Color;
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
 use union_find::{QuickUnionUf, UnionBySize, UnionFind};
 
-use anim::Animation;
-use tile::Tile;
+use event::GridEvent;
+use rules::{Blockers, Connectivity, Movement, Rules};
+use tile::{Moves, PALETTE, Tile};
+use validation::GridWarning;
 use vec_grid::VecGrid;
+use wall::{Side, Wall, WallKind};
+
+pub use vec_grid::FORMAT_VERSION;
 
 #[derive(Serialize, Deserialize, Clone)]
-#[serde(into = "VecGrid", from = "VecGrid")]
+#[serde(into = "VecGrid", try_from = "VecGrid")]
 pub struct Grid {
     pub steps: usize,
     pub tiles: Array2<Tile>,
-    #[serde(skip)]
-    pub active_animations: Vec<Animation>,
-    #[serde(skip)]
-    pub pending_pop: bool,
+    pub rules: Rules,
+    pub walls: Vec<Wall>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    // Every regular tile was cleared
+    Won,
+    // Out of steps, or no move changes the board
+    Lost,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LevelFormat {
+    Ron,
+    // One character per tile, see compact.rs
+    Compact,
+}
+
+impl LevelFormat {
+    // `.tiles` files are written in the compact format, everything else as RON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("tiles") => LevelFormat::Compact,
+            _ => LevelFormat::Ron,
+        }
+    }
+
+    // RON may open with comments, attributes or a struct name, so anything without the compact header is
+    // read as RON
+    pub fn detect(text: &str) -> Self {
+        match compact::has_header(text) {
+            true => LevelFormat::Compact,
+            false => LevelFormat::Ron,
+        }
+    }
+
+    // Files named `.tiles` are compact, others are detected by content since older compact files were
+    // saved under any name
+    pub fn of_file(path: &Path, text: &str) -> Self {
+        match Self::from_path(path) {
+            LevelFormat::Compact => LevelFormat::Compact,
+            LevelFormat::Ron => Self::detect(text),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MoveDir {
     Up,
     Down,
@@ -33,25 +89,75 @@ pub enum MoveDir {
     Right,
 }
 
+// Where a tile ended up after one step, and the portal it came out of if it went through one
+type Step = ((usize, usize), Option<(usize, usize)>);
+
 impl Grid {
     pub fn new(length: usize, width: usize, steps: usize) -> Self {
         Self {
             tiles: Array2::from_elem((length, width), Tile::Empty),
             steps,
-            active_animations: Vec::new(),
-            pending_pop: false,
+            rules: Rules::default(),
+            walls: Vec::new(),
         }
     }
 
     pub fn to_ron(&self) -> String {
         let pretty_config = PrettyConfig::new().depth_limit(2);
-        ron::ser::to_string_pretty(&self, pretty_config).unwrap()
+        ron::ser::to_string_pretty(&self, pretty_config).unwrap() + "\n"
     }
 
     pub fn from_ron(ron: &str) -> Result<Self> {
         Ok(ron::de::from_str(ron)?)
     }
 
+    // Fails for compact levels with more kinds of tile than the format has symbols for
+    pub fn to_text(&self, format: LevelFormat) -> Result<String> {
+        match format {
+            LevelFormat::Ron => Ok(self.to_ron()),
+            LevelFormat::Compact => Ok(compact::to_compact(self)?),
+        }
+    }
+
+    // Reads either format, telling them apart by content when there is no file name to go by
+    pub fn from_text(text: &str) -> Result<Self> {
+        Self::parse(text, LevelFormat::detect(text))
+    }
+
+    pub fn parse(text: &str, format: LevelFormat) -> Result<Self> {
+        match format {
+            LevelFormat::Ron => Self::from_ron(text),
+            LevelFormat::Compact => Ok(compact::from_compact(text)?),
+        }
+    }
+
+    // Short URL-safe text for pasting into chat. Fails for boards too large to encode.
+    pub fn to_share_code(&self) -> Result<String> {
+        Ok(share_code::to_share_code(self)?)
+    }
+
+    pub fn from_share_code(code: &str) -> Result<Self> {
+        Ok(share_code::from_share_code(code)?)
+    }
+
+    // The oldest format version that can hold this level, which is the one it is saved with
+    pub fn format_version(&self) -> u32 {
+        vec_grid::format_version(self)
+    }
+
+    // The format version a serialized grid was written with, 0 for files older than versioning
+    pub fn stored_version(text: &str, format: LevelFormat) -> Result<u32> {
+        match format {
+            LevelFormat::Ron => Ok(vec_grid::stored_version(text)?),
+            LevelFormat::Compact => Ok(compact::stored_version(text)?),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).wrap_err("Could not read file")?;
+        Self::parse(&content, LevelFormat::of_file(path, &content)).wrap_err("Invalid level file")
+    }
+
     pub fn get_height(&self) -> usize {
         self.tiles.dim().0
     }
@@ -64,136 +170,809 @@ impl Grid {
         self.tiles.view()
     }
 
-    pub fn get_anims_slice(&self) -> &[Animation] {
-        &self.active_animations
+    // Grows or shrinks the board from the bottom right corner, filling new cells with empty tiles
+    pub fn resize(&mut self, height: usize, width: usize) {
+        let mut tiles = Array2::from_elem((height, width), Tile::Empty);
+        let (keep_height, keep_width) = (height.min(self.get_height()), width.min(self.get_width()));
+        tiles
+            .slice_mut(s![..keep_height, ..keep_width])
+            .assign(&self.tiles.slice(s![..keep_height, ..keep_width]));
+        self.tiles = tiles;
+        self.walls.retain(|wall| {
+            let (y, x) = wall.far_cell();
+            y < height && x < width
+        });
     }
 
-    pub fn is_anim_completed(&self) -> bool {
-        self.active_animations.is_empty()
-    }
+    // FNV-1a hash of the board dimensions, tile layout, walls and rules. Every part is fed as fixed bytes, so
+    // it is stable across runs and builds, unlike tile ids.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut feed = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        };
 
-    // Returns true if move happened, false if no move happened
-    pub fn move_grid(&mut self, direction: MoveDir) -> bool {
-        if self.steps == 0 {
-            return false;
-        }
-        let mut moved = false;
-        match direction {
-            MoveDir::Left => {
-                for dx in 0..self.get_width() {
-                    for dy in 0..self.get_height() {
-                        moved |= self.move_tile(dy, dx, direction);
+        feed(&(self.get_height() as u64).to_le_bytes());
+        feed(&(self.get_width() as u64).to_le_bytes());
+        for tile in &self.tiles {
+            match tile {
+                Tile::Empty => feed(b"E"),
+                Tile::Blocker => feed(b"B"),
+                Tile::Regular { color, moves, .. } => {
+                    feed(b"R");
+                    feed(color.to_string().as_bytes());
+                    match moves {
+                        Moves::Any => {}
+                        Moves::Anchored => feed(b"a"),
+                        Moves::Horizontal => feed(b"h"),
+                        Moves::Vertical => feed(b"v"),
                     }
                 }
-            }
-            MoveDir::Right => {
-                for dx in (0..self.get_width()).rev() {
-                    for dy in 0..self.get_height() {
-                        moved |= self.move_tile(dy, dx, direction);
-                    }
+                Tile::Wildcard => feed(b"W"),
+                Tile::Bomb { color, .. } => {
+                    feed(b"X");
+                    feed(color.to_string().as_bytes());
                 }
-            }
-            MoveDir::Up => {
-                for dy in 0..self.get_height() {
-                    for dx in 0..self.get_width() {
-                        moved |= self.move_tile(dy, dx, direction);
-                    }
+                Tile::Ice { color, .. } => {
+                    feed(b"I");
+                    feed(color.to_string().as_bytes());
                 }
-            }
-            MoveDir::Down => {
-                for dy in (0..self.get_height()).rev() {
-                    for dx in 0..self.get_width() {
-                        moved |= self.move_tile(dy, dx, direction);
-                    }
+                Tile::Portal { pair } => {
+                    feed(b"P");
+                    feed(&[*pair]);
                 }
+                Tile::Crate => feed(b"C"),
+                Tile::Void => feed(b"V"),
             }
-        };
-        if moved {
-            self.steps = self.steps.saturating_sub(1);
-            true
+        }
+        for wall in &self.walls {
+            feed(&(wall.at.0 as u64).to_le_bytes());
+            feed(&(wall.at.1 as u64).to_le_bytes());
+            feed(match wall.side {
+                Side::Right => b"r",
+                Side::Below => b"b",
+            });
+            match wall.kind {
+                WallKind::Solid => feed(b"S"),
+                WallKind::OneWay(direction) => feed(match direction {
+                    MoveDir::Up => b"U",
+                    MoveDir::Down => b"D",
+                    MoveDir::Left => b"L",
+                    MoveDir::Right => b"R",
+                }),
+            }
+        }
+        feed(&(self.rules.min_group as u64).to_le_bytes());
+        feed(match self.rules.connectivity {
+            Connectivity::Four => b"4",
+            Connectivity::Eight => b"8",
+        });
+        feed(match self.rules.blockers {
+            Blockers::Separate => b"s",
+            Blockers::Count => b"c",
+        });
+        feed(match self.rules.movement {
+            Movement::Step => b"t",
+            Movement::Slide => b"l",
+        });
+        feed(&[self.rules.cascade as u8, self.rules.wrap as u8]);
+        hash
+    }
+
+    // Issues that leave the level loadable but unwinnable or confusing to look at
+    pub fn warnings(&self) -> Vec<GridWarning> {
+        let mut warnings = Vec::new();
+        if self.steps == 0 && !self.is_cleared() {
+            warnings.push(GridWarning::NoSteps);
+        }
+
+        let needed = self.rules.min_group;
+        warnings.extend(
+            self.short_colors()
+                .into_iter()
+                .map(|(color, count)| GridWarning::TooFewTiles { color, count, needed }),
+        );
+
+        // Only the first tile of each off-palette color is reported
+        let mut off_palette = Vec::new();
+        for ((y, x), tile) in self.tiles.indexed_iter() {
+            if let Some(color) = tile.color()
+                && !PALETTE.contains(&color)
+                && !off_palette.contains(&color)
+            {
+                off_palette.push(color);
+                warnings.push(GridWarning::OffPalette { color, at: (y, x) });
+            }
+        }
+        warnings
+    }
+
+    // Colors with too few tiles to ever make a group big enough to clear, and how many tiles they have.
+    // Blockers that count toward groups can make up the difference and a bomb's blast clears tiles outside
+    // its group, so with either no color falls short. Wildcards could join any color, so they are added to
+    // every count.
+    pub fn short_colors(&self) -> Vec<(Color, usize)> {
+        let bombs = self.tiles.iter().any(|tile| matches!(tile, Tile::Bomb { .. }));
+        if self.rules.blockers == Blockers::Count || bombs {
+            return Vec::new();
+        }
+        let wildcards = self.tiles.iter().filter(|tile| matches!(tile, Tile::Wildcard)).count();
+
+        let mut counts: Vec<(Color, usize)> = Vec::new();
+        for color in self.tiles.iter().filter_map(Tile::color) {
+            match counts.iter_mut().find(|(c, _)| *c == color) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((color, 1)),
+            }
+        }
+        counts.retain(|&(_, count)| count + wildcards < self.rules.min_group);
+        counts
+    }
+
+    // Every colored tile is gone. Wildcards may be left over.
+    pub fn is_cleared(&self) -> bool {
+        !self.tiles.iter().any(|tile| tile.color().is_some())
+    }
+
+    pub fn has_legal_move(&self) -> bool {
+        [MoveDir::Up, MoveDir::Down, MoveDir::Left, MoveDir::Right]
+            .into_iter()
+            .any(|direction| self.clone().apply_move(direction).is_some())
+    }
+
+    // Returns None while the game is still going
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.is_cleared() {
+            Some(Outcome::Won)
+        } else if !self.has_legal_move() {
+            Some(Outcome::Lost)
         } else {
-            false
+            None
         }
     }
 
-    // Returns true if tile moved, false if tile did not move
-    fn move_tile(&mut self, y: usize, x: usize, direction: MoveDir) -> bool {
-        let (ty, tx) = match direction {
-            MoveDir::Left => (y, x.wrapping_sub(1)),  // (y, x - 1)
-            MoveDir::Right => (y, x.wrapping_add(1)), // (y, x + 1)
-            MoveDir::Up => (y.wrapping_sub(1), x),    // (y - 1, x)
-            MoveDir::Down => (y.wrapping_add(1), x),  // (y + 1, x)
-        };
+    // Slides every tile one cell, or as far as it goes when tiles slide, then clears every group big enough
+    // under the level's rules. With cascades, the remaining tiles then settle in the same direction and
+    // any groups that forms clear as a further wave, until a wave clears nothing.
+    // Returns None if nothing moved, otherwise the events in the order they happened.
+    pub fn apply_move(&mut self, direction: MoveDir) -> Option<Vec<GridEvent>> {
+        if self.steps == 0 {
+            return None;
+        }
+        let mut events = self.slide_tiles(direction, self.rules.movement);
+        if events.is_empty() {
+            return None;
+        }
+        for wave in 1.. {
+            let cleared = self.pop_connected_tiles(wave);
+            if cleared.is_empty() || !self.rules.cascade {
+                events.extend(cleared);
+                break;
+            }
+            events.extend(cleared);
+            // Settling always goes all the way, like gravity, whatever the movement rule
+            let settled = self.slide_tiles(direction, Movement::Slide);
+            if settled.is_empty() {
+                break;
+            }
+            events.extend(settled);
+        }
+        self.steps = self.steps.saturating_sub(1);
+        Some(events)
+    }
 
-        let from = self.tiles.get((y, x)).unwrap();
-        let Some(to) = self.tiles.get((ty, tx)) else {
-            // Hit the wall
-            return false;
+    fn slide_tiles(&mut self, direction: MoveDir, movement: Movement) -> Vec<GridEvent> {
+        let (height, width) = (self.get_height(), self.get_width());
+        // Leading edge first, so each tile moves after whatever is in front of it
+        let order: Vec<(usize, usize)> = match direction {
+            MoveDir::Left => (0..width).flat_map(|x| (0..height).map(move |y| (y, x))).collect(),
+            MoveDir::Right => (0..width)
+                .rev()
+                .flat_map(|x| (0..height).map(move |y| (y, x)))
+                .collect(),
+            MoveDir::Up => (0..height).flat_map(|y| (0..width).map(move |x| (y, x))).collect(),
+            MoveDir::Down => (0..height)
+                .rev()
+                .flat_map(|y| (0..width).map(move |x| (y, x)))
+                .collect(),
         };
 
-        // Target is regular or blocker tile: cannot move
-        // Origin is empty or blocker tile: cannot move
-        if let (Tile::Regular { .. }, Tile::Empty) = (from, to) {
-            self.active_animations.push(Animation::Moving {
-                tile: *from,
-                from: (y, x),
-                direction,
-                start_time: Instant::now(),
-            });
-            self.tiles.swap((y, x), (ty, tx));
-            self.pending_pop = true;
-            true
+        let mut moved = Vec::new();
+        // Where tiles that already moved ended up, so none moves twice in one go
+        let mut settled = HashSet::new();
+        loop {
+            let before = moved.len();
+            for &(y, x) in &order {
+                if settled.contains(&(y, x)) {
+                    continue;
+                }
+                if let Some(event @ GridEvent::Slide { to, .. }) = self.move_tile(y, x, direction, movement) {
+                    settled.insert(to);
+                    moved.push(event);
+                }
+            }
+            // Edges that wrap have no leading edge, and a tile may only get room once the one in front of it
+            // has come round, so keep sweeping until nothing moves
+            if !self.rules.wrap || moved.len() == before {
+                break;
+            }
+        }
+        moved
+    }
+
+    // Returns Some containing a slide event if tile moved, None if tile did not move.
+    // The tile moves one cell, or under sliding movement as far as it can go.
+    fn move_tile(&mut self, y: usize, x: usize, direction: MoveDir, movement: Movement) -> Option<GridEvent> {
+        let tile = self.tiles[[y, x]];
+        let mut at = (y, x);
+        let mut through = None;
+        while let Some((next, portal)) = self.step_tile(at, direction) {
+            at = next;
+            through = portal.or(through);
+            // All the way round wrapping edges or a loop of portals with nothing to stop it, so it stays put
+            if at == (y, x) {
+                break;
+            }
+            if movement == Movement::Step {
+                break;
+            }
+        }
+        (at != (y, x)).then_some(GridEvent::Slide {
+            tile,
+            from: (y, x),
+            to: at,
+            direction,
+            through,
+        })
+    }
+
+    // Moves the tile at (y, x) one cell, returning where it ended up and the portal it came out of if it
+    // went through one, or None if it can't move
+    fn step_tile(&mut self, at: (usize, usize), direction: MoveDir) -> Option<Step> {
+        // Origin is empty, fixed in place or restricted to the other axis: cannot move
+        if !self.tiles[at].can_move(direction) {
+            return None;
+        }
+        // Hit the edge of the board, or a wall or gate in the way
+        let mut target = self.next_cell(at, direction)?;
+        if self.is_walled(at, direction) {
+            return None;
+        }
+        let mut through = None;
+        if let Tile::Portal { pair } = self.tiles[target] {
+            let exit = self.partner_portal(target, pair)?;
+            target = self.next_cell(exit, direction)?;
+            if self.is_walled(exit, direction) {
+                return None;
+            }
+            through = Some(exit);
+        }
+
+        // Target is taken: cannot move
+        if let Tile::Empty = self.tiles[target] {
+            self.tiles.swap(at, target);
+            Some((target, through))
         } else {
-            false
+            None
         }
     }
 
-    pub fn update_anim_state(&mut self) {
-        self.clear_completed_animations();
+    // The cell one step from `at` in `direction`, None past the edge
+    fn next_cell(&self, at: (usize, usize), direction: MoveDir) -> Option<(usize, usize)> {
+        let (dy, dx) = match direction {
+            MoveDir::Left => (0, -1),
+            MoveDir::Right => (0, 1),
+            MoveDir::Up => (-1, 0),
+            MoveDir::Down => (1, 0),
+        };
+        self.offset_cell(at, dy, dx)
+    }
 
-        if self.active_animations.is_empty() && self.pending_pop {
-            self.pending_pop = false;
-            self.pop_connected_tiles(); // Populates animations again
+    // (y + dy, x + dx), coming back in on the opposite side when edges wrap and None past them otherwise
+    fn offset_cell(&self, (y, x): (usize, usize), dy: isize, dx: isize) -> Option<(usize, usize)> {
+        let (height, width) = self.tiles.dim();
+        if self.rules.wrap {
+            return Some((
+                (y + height).wrapping_add_signed(dy) % height,
+                (x + width).wrapping_add_signed(dx) % width,
+            ));
         }
+        let cell = (y.checked_add_signed(dy)?, x.checked_add_signed(dx)?);
+        self.tiles.get(cell).map(|_| cell)
     }
 
-    fn clear_completed_animations(&mut self) {
-        self.active_animations.retain(|anim| anim.is_active());
+    // Whether a wall, or a gate facing the other way, stops a tile leaving `from` in `direction`
+    fn is_walled(&self, from: (usize, usize), direction: MoveDir) -> bool {
+        self.walls.iter().any(|wall| wall.blocks(from, direction))
     }
 
-    fn pop_connected_tiles(&mut self) {
-        let (length, width) = (self.get_height(), self.get_width());
-        let mut uf = QuickUnionUf::<UnionBySize>::new(length * width);
-        for ((y, x), tile) in self.tiles.indexed_iter() {
-            let Tile::Regular { color, .. } = tile else {
+    // The other portal of a pair. Validation guarantees there is exactly one.
+    fn partner_portal(&self, at: (usize, usize), pair: u8) -> Option<(usize, usize)> {
+        self.tiles
+            .indexed_iter()
+            .find(|&(other, tile)| other != at && matches!(tile, Tile::Portal { pair: p } if *p == pair))
+            .map(|(other, _)| other)
+    }
+
+    // Ice in a clearing group cracks into a regular tile instead of clearing
+    fn pop_connected_tiles(&mut self, wave: usize) -> Vec<GridEvent> {
+        let mut events = Vec::new();
+        for (group, tiles) in self.find_poppable_groups().into_iter().enumerate() {
+            for at in tiles {
+                let tile = self.tiles[at];
+                events.push(match tile {
+                    Tile::Ice { id, color } => {
+                        self.tiles[at] = Tile::Regular {
+                            id,
+                            color,
+                            moves: Moves::Any,
+                        };
+                        GridEvent::Crack { tile, at, wave, group }
+                    }
+                    _ => {
+                        self.tiles[at] = Tile::Empty;
+                        GridEvent::Clear { tile, at, wave, group }
+                    }
+                });
+            }
+        }
+        events
+    }
+
+    // Returns the coordinates of the tiles in every group that clears, each listed with the first group
+    // that takes it: groups of at least `min_group` tiles, the blockers touching them when blockers count,
+    // and everything caught in the blast of a bomb among them.
+    fn find_poppable_groups(&self) -> Vec<Vec<(usize, usize)>> {
+        // Without wildcards every color can be grouped in one pass. A wildcard can join a group of any
+        // color, so then each color gets its own pass.
+        let passes = match self.tiles.iter().any(|tile| matches!(tile, Tile::Wildcard)) {
+            false => vec![None],
+            true => {
+                let mut colors: Vec<Color> = Vec::new();
+                for color in self.tiles.iter().filter_map(Tile::color) {
+                    if !colors.contains(&color) {
+                        colors.push(color);
+                    }
+                }
+                colors.into_iter().map(Some).collect()
+            }
+        };
+
+        let mut claimed = HashSet::new();
+        let mut groups = Vec::new();
+        for color in passes {
+            for group in self.find_groups(color) {
+                let group: Vec<_> = group.into_iter().filter(|&at| claimed.insert(at)).collect();
+                if !group.is_empty() {
+                    groups.push(group);
+                }
+            }
+        }
+        for group in &mut groups {
+            self.detonate(group, &mut claimed);
+        }
+        groups
+    }
+
+    // Groups big enough to clear. With a color, groups of that color and wildcards; without one, groups of
+    // every color at once, leaving wildcards out. A group of only wildcards never clears.
+    fn find_groups(&self, color: Option<Color>) -> Vec<Vec<(usize, usize)>> {
+        let member = |tile: &Tile| match color {
+            Some(color) => tile.color() == Some(color) || matches!(tile, Tile::Wildcard),
+            None => tile.color().is_some(),
+        };
+        let width = self.get_width();
+        let index = |(y, x): (usize, usize)| y * width + x;
+        let mut uf = QuickUnionUf::<UnionBySize>::new(self.tiles.len());
+        for (at, tile) in self.tiles.indexed_iter() {
+            if !member(tile) {
                 continue;
-            };
-            let index = y * width + x;
+            }
+            for neighbor in self.neighbors(at) {
+                let other = &self.tiles[neighbor];
+                if member(other) && (color.is_some() || other.color() == tile.color()) {
+                    uf.union(index(at), index(neighbor));
+                }
+            }
+        }
 
-            let neighbors = [(x + 1, y, index + 1), (x, y + 1, index + width)]; // Right, Down
-            for (nx, ny, n_index) in neighbors {
-                if matches!(&self.tiles.get((ny, nx)), Some(Tile::Regular { color: c, .. }) if c == color) {
-                    uf.union(index, n_index);
+        // Group root -> blockers touching the group. A blocker next to two groups counts toward both.
+        let mut touching: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+        if self.rules.blockers == Blockers::Count {
+            for (at, tile) in self.tiles.indexed_iter() {
+                if !matches!(tile, Tile::Blocker) {
+                    continue;
+                }
+                let mut roots: Vec<usize> = self
+                    .neighbors(at)
+                    .filter(|&neighbor| member(&self.tiles[neighbor]))
+                    .map(|neighbor| uf.find(index(neighbor)))
+                    .collect();
+                roots.sort_unstable();
+                roots.dedup();
+                for root in roots {
+                    touching.entry(root).or_default().push(at);
                 }
             }
         }
 
-        for ((y, x), tile) in self.tiles.indexed_iter_mut() {
-            let Tile::Regular { .. } = tile else {
+        // Keyed by root so groups come out in board order
+        let mut groups: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+        let mut colored = HashSet::new();
+        for (at, tile) in self.tiles.indexed_iter() {
+            if !member(tile) {
                 continue;
-            };
-
-            let index = y * width + x;
-            let root_index = uf.find(index);
-            if uf.get(root_index).size() >= 4 {
-                self.active_animations.push(Animation::Clearing {
-                    tile: *tile,
-                    at: (y, x),
-                    start_time: Instant::now(),
-                });
+            }
+            let root = uf.find(index(at));
+            let blockers = touching.get(&root).map_or(0, Vec::len);
+            if uf.get(root).size() + blockers >= self.rules.min_group {
+                groups.entry(root).or_default().push(at);
+                if tile.color().is_some() {
+                    colored.insert(root);
+                }
+            }
+        }
 
-                *tile = Tile::Empty;
+        groups.retain(|root, _| colored.contains(root));
+        for (root, tiles) in &mut groups {
+            tiles.extend(touching.remove(root).unwrap_or_default());
+        }
+        groups.into_values().collect()
+    }
+
+    // Adds everything around each bomb in the group to it, setting off any bomb caught in the blast.
+    // Crates come through unharmed, and voids aren't part of the board to blow up.
+    fn detonate(&self, group: &mut Vec<(usize, usize)>, claimed: &mut HashSet<(usize, usize)>) {
+        let mut next = 0;
+        while let Some(&at) = group.get(next) {
+            next += 1;
+            if !matches!(self.tiles[at], Tile::Bomb { .. }) {
+                continue;
+            }
+            for neighbor in self.cells_around(at, Connectivity::Eight) {
+                let spared = matches!(
+                    self.tiles[neighbor],
+                    Tile::Empty | Tile::Portal { .. } | Tile::Crate | Tile::Void
+                );
+                if !spared && claimed.insert(neighbor) {
+                    group.push(neighbor);
+                }
             }
         }
     }
+
+    // Cells next to `at` under the level's connectivity
+    fn neighbors(&self, at: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells_around(at, self.rules.connectivity)
+    }
+
+    // Cells next to `at`, clipped to the board unless its edges wrap
+    fn cells_around(
+        &self,
+        at: (usize, usize),
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        connectivity
+            .offsets()
+            .iter()
+            .filter_map(move |&(dy, dx)| self.offset_cell(at, dy, dx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validation::GridError;
+
+    pub(super) fn level(text: &str) -> Grid {
+        Grid::from_text(text).unwrap()
+    }
+
+    // For round trips through a format, with `text` being what `reread` was read back from
+    pub(super) fn assert_same_level(reread: &Grid, grid: &Grid, text: &str) {
+        assert_eq!(reread.fingerprint(), grid.fingerprint(), "changed the board:\n{text}");
+        assert_eq!(reread.steps, grid.steps, "changed the steps:\n{text}");
+    }
+
+    pub(super) fn error(loaded: Result<Grid, GridError>) -> GridError {
+        loaded.err().expect("level should not have loaded")
+    }
+
+    // The board in the compact format, without its header
+    fn board(grid: &Grid) -> String {
+        let text = grid.to_text(LevelFormat::Compact).unwrap();
+        text.split_once("\n\n").unwrap().1.to_string()
+    }
+
+    fn describe(events: &[GridEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match *event {
+                GridEvent::Slide { from, to, .. } => format!("slide {from:?} -> {to:?}"),
+                GridEvent::Clear { at, wave, group, .. } => format!("clear {at:?} in {wave}.{group}"),
+                GridEvent::Crack { at, wave, group, .. } => format!("crack {at:?} in {wave}.{group}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tiles_slide_one_cell() {
+        let mut grid = level("steps: 3\n\nR..G\n.R..\n");
+        let events = grid.apply_move(MoveDir::Right).unwrap();
+        assert_eq!(board(&grid), ".R.G\n..R.\n");
+        assert_eq!(describe(&events), ["slide (1, 1) -> (1, 2)", "slide (0, 0) -> (0, 1)"]);
+        assert_eq!(grid.steps, 2);
+    }
+
+    #[test]
+    fn groups_big_enough_clear() {
+        let mut grid = level("steps: 3\n\nRRR.R\n");
+        let events = grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), ".....\n");
+        assert_eq!(
+            describe(&events),
+            [
+                "slide (0, 4) -> (0, 3)",
+                "clear (0, 0) in 1.0",
+                "clear (0, 1) in 1.0",
+                "clear (0, 2) in 1.0",
+                "clear (0, 3) in 1.0",
+            ]
+        );
+        assert_eq!(grid.outcome(), Some(Outcome::Won));
+
+        // Three is one short of a group
+        let mut grid = level("steps: 3\n\nRR.R\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "RRR.\n");
+    }
+
+    #[test]
+    fn blocked_moves_change_nothing() {
+        let mut grid = level("steps: 3\n\nR#\n");
+        for direction in [MoveDir::Up, MoveDir::Down, MoveDir::Left, MoveDir::Right] {
+            assert!(grid.apply_move(direction).is_none());
+        }
+        assert_eq!(board(&grid), "R#\n");
+        assert_eq!(grid.steps, 3);
+        assert_eq!(grid.outcome(), Some(Outcome::Lost));
+
+        // Nothing moves without steps left either
+        let mut grid = level("steps: 0\n\nR.\n");
+        assert!(grid.apply_move(MoveDir::Right).is_none());
+        assert_eq!(board(&grid), "R.\n");
+    }
+
+    #[test]
+    fn events_come_leading_edge_first_then_clears_in_board_order() {
+        let mut grid = level("steps: 3\n\nR.R.\n.RR.\n..R.\n");
+        let events = grid.apply_move(MoveDir::Right).unwrap();
+        assert_eq!(board(&grid), ".R..\n....\n....\n");
+        assert_eq!(
+            describe(&events),
+            [
+                "slide (0, 2) -> (0, 3)",
+                "slide (1, 2) -> (1, 3)",
+                "slide (2, 2) -> (2, 3)",
+                "slide (1, 1) -> (1, 2)",
+                "slide (0, 0) -> (0, 1)",
+                "clear (0, 3) in 1.0",
+                "clear (1, 2) in 1.0",
+                "clear (1, 3) in 1.0",
+                "clear (2, 3) in 1.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprint_tells_move_restrictions_and_walls_apart() {
+        let levels = [
+            "steps: 1\n\nR.\n",
+            "steps: 1\nanchored a: Red\n\na.\n",
+            "steps: 1\nhorizontal a: Red\n\na.\n",
+            "steps: 1\nvertical a: Red\n\na.\n",
+            "steps: 1\nwall: 1 1 right\n\nR.\n",
+            "steps: 1\ngate: 1 1 right\n\nR.\n",
+            "steps: 1\ngate: 1 2 left\n\nR.\n",
+        ];
+        let fingerprints: HashSet<u64> = levels.iter().map(|text| level(text).fingerprint()).collect();
+        assert_eq!(fingerprints.len(), levels.len());
+    }
+
+    #[test]
+    fn rules_decide_group_size_connectivity_and_blockers() {
+        let mut grid = level("steps: 3\nmin_group: 3\n\nRR.R\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "....\n");
+
+        // Corners only join groups with 8-way connectivity
+        for (connectivity, after) in [("4", "R.R\n.R.\n...\n"), ("8", "...\n...\n...\n")] {
+            let mut grid = level(&format!(
+                "steps: 3\nmin_group: 3\nconnectivity: {connectivity}\n\nR.R\n...\n.R.\n"
+            ));
+            grid.apply_move(MoveDir::Up).unwrap();
+            assert_eq!(board(&grid), after, "{connectivity}-way");
+        }
+
+        // Counted blockers make up the group and clear with it
+        let mut grid = level("steps: 3\nblockers: count\n\n#RR.R\n");
+        let events = grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), ".....\n");
+        assert_eq!(describe(&events).last().unwrap(), "clear (0, 0) in 1.0");
+        let mut grid = level("steps: 3\n\n#RR.R\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "#RRR.\n");
+    }
+
+    #[test]
+    fn tiles_slide_until_blocked_under_slide_movement() {
+        let mut grid = level("steps: 3\nmovement: slide\n\nR..#.G\nR.R...\n");
+        let events = grid.apply_move(MoveDir::Right).unwrap();
+        assert_eq!(board(&grid), "..R#.G\n....RR\n");
+        assert_eq!(
+            describe(&events),
+            [
+                "slide (1, 2) -> (1, 5)",
+                "slide (0, 0) -> (0, 2)",
+                "slide (1, 0) -> (1, 4)",
+            ]
+        );
+        // A whole slide is still one step
+        assert_eq!(grid.steps, 2);
+    }
+
+    #[test]
+    fn cascades_settle_the_board_and_clear_again() {
+        let mut grid = level("steps: 3\nmin_group: 3\ncascade: true\n\nRR.RGG.G\n");
+        let events = grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "........\n");
+        assert_eq!(
+            describe(&events),
+            [
+                "slide (0, 3) -> (0, 2)",
+                "slide (0, 4) -> (0, 3)",
+                "slide (0, 5) -> (0, 4)",
+                "slide (0, 7) -> (0, 6)",
+                "clear (0, 0) in 1.0",
+                "clear (0, 1) in 1.0",
+                "clear (0, 2) in 1.0",
+                "slide (0, 3) -> (0, 0)",
+                "slide (0, 4) -> (0, 1)",
+                "slide (0, 6) -> (0, 2)",
+                "clear (0, 0) in 2.0",
+                "clear (0, 1) in 2.0",
+                "clear (0, 2) in 2.0",
+            ]
+        );
+        assert_eq!(grid.steps, 2);
+
+        // Without cascades the greens are left where the first clear left them
+        let mut grid = level("steps: 3\nmin_group: 3\n\nRR.RGG.G\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "...GG.G.\n");
+    }
+
+    #[test]
+    fn special_tiles_join_blast_crack_and_teleport() {
+        // A wildcard makes up any color's group, but wildcards alone never clear
+        let mut grid = level("steps: 3\n\nRRR.*\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), ".....\n");
+        let mut grid = level("steps: 3\n\n**.**\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "****.\n");
+
+        // A bomb clearing takes everything around it with it
+        let mut grid = level("steps: 3\nbomb b: Red\n\nRRR.b\nGG#BY\n");
+        let events = grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), ".....\nGG...\n");
+        assert_eq!(
+            describe(&events)[5..],
+            ["clear (1, 3) in 1.0", "clear (1, 2) in 1.0", "clear (1, 4) in 1.0"]
+        );
+
+        // Ice in a clearing group cracks into a regular tile of its color
+        let mut grid = level("steps: 3\nice c: Blue\n\nBBB.c\n");
+        let events = grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "...B.\n");
+        assert_eq!(describe(&events).last().unwrap(), "crack (0, 3) in 1.0");
+
+        // A tile entering a portal comes out past its partner, and the portals stay put
+        let mut grid = level("steps: 3\nportal d: 1\n\nRd.d.\n");
+        let events = grid.apply_move(MoveDir::Right).unwrap();
+        assert_eq!(board(&grid), ".a.aR\n");
+        assert!(matches!(
+            events[..],
+            [GridEvent::Slide {
+                from: (0, 0),
+                to: (0, 4),
+                through: Some((0, 3)),
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn crates_walls_and_gates_stop_tiles() {
+        // Crates move with the board but never join a group
+        let mut grid = level("steps: 3\n\nRR+.RR\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "RR+RR.\n");
+
+        // Walls stop tiles but not groups
+        let mut grid = level("steps: 3\nwall: 1 4 right\n\nRRR.R\n");
+        assert!(grid.apply_move(MoveDir::Left).is_none());
+        let mut grid = level("steps: 3\nwall: 1 2 right\n\nRRRR\nG...\n");
+        grid.apply_move(MoveDir::Right).unwrap();
+        assert_eq!(board(&grid), "....\n.G..\n");
+
+        // Gates only let tiles through one way
+        let mut grid = level("steps: 3\ngate: 1 1 right\n\nR.\n");
+        grid.apply_move(MoveDir::Right).unwrap();
+        assert_eq!(board(&grid), ".R\n");
+        assert!(grid.apply_move(MoveDir::Left).is_none());
+    }
+
+    #[test]
+    fn restricted_tiles_only_move_along_their_axis() {
+        let mut grid = level("steps: 3\nanchored a: Red\nhorizontal b: Green\nvertical c: Blue\n\nabc\n...\n");
+        grid.apply_move(MoveDir::Down).unwrap();
+        assert_eq!(board(&grid), "ab.\n..c\n");
+        grid.apply_move(MoveDir::Right).unwrap();
+        assert_eq!(board(&grid), "a.b\n..c\n");
+        // Nothing else is on the board to move
+        let mut grid = level("steps: 3\nanchored a: Red\n\n.a\n");
+        assert!(grid.apply_move(MoveDir::Left).is_none());
+
+        // Anchored tiles still clear with their group
+        let mut grid = level("steps: 3\nanchored a: Red\n\naRR.R\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), ".....\n");
+    }
+
+    #[test]
+    fn void_cells_are_outside_the_board() {
+        // Tiles can't move into a void, and groups don't reach across one
+        let mut grid = level("steps: 3\nmin_group: 3\n\nRR~.R\n");
+        assert!(grid.apply_move(MoveDir::Right).is_none());
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "RR~R.\n");
+
+        // A bomb's blast leaves voids be
+        let mut grid = level("steps: 3\nbomb b: Red\n\nRRR.b\n~~~~~\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), ".....\n~~~~~\n");
+    }
+
+    #[test]
+    fn wrapping_edges_join_up_for_moves_and_groups() {
+        let mut grid = level("steps: 3\nwrap: true\n\n.R\n");
+        let events = grid.apply_move(MoveDir::Right).unwrap();
+        assert_eq!(board(&grid), "R.\n");
+        assert_eq!(describe(&events), ["slide (0, 1) -> (0, 0)"]);
+
+        // A tile only comes round once the one in front of it has made room
+        let mut grid = level("steps: 3\nwrap: true\n\nRR..R\n");
+        grid.apply_move(MoveDir::Left).unwrap();
+        assert_eq!(board(&grid), "R..RR\n");
+
+        // The red tiles at either end are next to each other
+        for (wrap, after) in [("false", "a.aa\n.G..\n"), ("true", "....\n.G..\n")] {
+            let mut grid = level(&format!(
+                "steps: 3\nmin_group: 3\nwrap: {wrap}\nanchored a: Red\n\na.aa\nG...\n"
+            ));
+            grid.apply_move(MoveDir::Right).unwrap();
+            assert_eq!(board(&grid), after, "wrap: {wrap}");
+        }
+    }
+
+    // Progress is saved under the fingerprint, so changing how it is worked out loses every record
+    #[test]
+    fn fingerprint_stays_the_same_between_builds() {
+        let grid = level("steps: 3\nmin_group: 3\ngate: 1 1 right\nanchored a: Red\n\nRa.\n#GB\n");
+        assert_eq!(format!("{:016x}", grid.fingerprint()), "b26766ce6b9bfded");
+    }
 }
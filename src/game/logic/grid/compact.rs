@@ -0,0 +1,456 @@
+use ratatui::style::Color;
+
+use super::{
+    Grid, MoveDir,
+    rules::Rules,
+    tile::{Moves, TileKind},
+    validation::GridError,
+    vec_grid::{VecGrid, format_version},
+    wall::{Wall, WallKind},
+};
+
+// One character per tile, e.g.
+//
+//     version: 1
+//     steps: 5
+//
+//     ..R.
+//     GR.G
+//     #.RB
+//
+// Colors outside the default legend are declared in the header as `color a: LightRed`, and so are bombs,
+// ice and portals: `bomb b: Red`, `ice c: Blue`, `portal d: 1`. Regular tiles that can't move every way
+// are declared by what they can do: `anchored e: Red`, `horizontal f: Green`, `vertical g: Blue`.
+// `*` is a wildcard, `+` a crate, and `~` a void cell outside the board, so boards can take any shape.
+//
+// Rules that differ from the classic ones get a header line each: `min_group: 3`, `connectivity: 8`,
+// `blockers: count`, `movement: slide`, `cascade: true`, `wrap: true`.
+//
+// Walls between cells are header lines too, naming a cell (1-based) and the side of it the wall is on:
+// `wall: 2 3 right`. A gate names the cell and direction a tile crosses it in: `gate: 2 3 down`.
+const LEGEND: [(char, TileKind); 11] = [
+    ('.', TileKind::Empty),
+    ('~', TileKind::Void),
+    ('#', TileKind::Blocker),
+    ('*', TileKind::Wildcard),
+    ('+', TileKind::Crate),
+    ('R', TileKind::Regular(Color::Red, Moves::Any)),
+    ('G', TileKind::Regular(Color::Green, Moves::Any)),
+    ('B', TileKind::Regular(Color::Blue, Moves::Any)),
+    ('Y', TileKind::Regular(Color::Yellow, Moves::Any)),
+    ('M', TileKind::Regular(Color::Magenta, Moves::Any)),
+    ('C', TileKind::Regular(Color::Cyan, Moves::Any)),
+];
+
+// Keys of the header lines, the first word before the colon
+const HEADER_KEYS: [&str; 17] = [
+    "version",
+    "steps",
+    "min_group",
+    "connectivity",
+    "blockers",
+    "movement",
+    "cascade",
+    "wrap",
+    "wall",
+    "gate",
+    "color",
+    "bomb",
+    "ice",
+    "portal",
+    "anchored",
+    "horizontal",
+    "vertical",
+];
+
+// Compact text opens with a header line such as `version: 1`, which no RON document can start with
+pub fn has_header(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .and_then(|line| line.split_once(':'))
+        .and_then(|(key, _)| key.split_whitespace().next())
+        .is_some_and(|key| HEADER_KEYS.contains(&key))
+}
+
+pub fn to_compact(grid: &Grid) -> Result<String, GridError> {
+    let mut declared = Vec::new();
+    for kind in grid.tiles.iter().map(|tile| tile.kind()) {
+        if !LEGEND.iter().any(|(_, k)| *k == kind) && !declared.contains(&kind) {
+            declared.push(kind);
+        }
+    }
+    // Lowercase letters and digits are free for declared tiles since the default legend is uppercase
+    let symbols = ('a'..='z').chain('0'..='9');
+    if declared.len() > symbols.clone().count() {
+        return Err(GridError::TooManyKinds {
+            count: declared.len(),
+            symbols: symbols.count(),
+        });
+    }
+    let declared: Vec<_> = symbols.zip(declared).collect();
+    let mut legend = LEGEND.to_vec();
+    legend.extend(&declared);
+
+    let mut text = format!("version: {}\nsteps: {}\n", format_version(grid), grid.steps);
+    let defaults = Rules::default();
+    if grid.rules.min_group != defaults.min_group {
+        text += &format!("min_group: {}\n", grid.rules.min_group);
+    }
+    if grid.rules.connectivity != defaults.connectivity {
+        text += &format!("connectivity: {}\n", grid.rules.connectivity);
+    }
+    if grid.rules.blockers != defaults.blockers {
+        text += &format!("blockers: {}\n", grid.rules.blockers);
+    }
+    if grid.rules.movement != defaults.movement {
+        text += &format!("movement: {}\n", grid.rules.movement);
+    }
+    if grid.rules.cascade != defaults.cascade {
+        text += &format!("cascade: {}\n", grid.rules.cascade);
+    }
+    if grid.rules.wrap != defaults.wrap {
+        text += &format!("wrap: {}\n", grid.rules.wrap);
+    }
+    for (symbol, kind) in declared {
+        text += &match kind {
+            TileKind::Bomb(color) => format!("bomb {symbol}: {color}\n"),
+            TileKind::Ice(color) => format!("ice {symbol}: {color}\n"),
+            TileKind::Portal(pair) => format!("portal {symbol}: {pair}\n"),
+            TileKind::Regular(color, Moves::Any) => format!("color {symbol}: {color}\n"),
+            TileKind::Regular(color, Moves::Anchored) => format!("anchored {symbol}: {color}\n"),
+            TileKind::Regular(color, Moves::Horizontal) => format!("horizontal {symbol}: {color}\n"),
+            TileKind::Regular(color, Moves::Vertical) => format!("vertical {symbol}: {color}\n"),
+            // Always in the default legend
+            TileKind::Empty | TileKind::Blocker | TileKind::Wildcard | TileKind::Crate | TileKind::Void => continue,
+        };
+    }
+    for wall in &grid.walls {
+        let (key, (y, x), direction) = match wall.kind {
+            WallKind::Solid => ("wall", wall.at, wall.direction()),
+            WallKind::OneWay(direction @ (MoveDir::Right | MoveDir::Down)) => ("gate", wall.at, direction),
+            WallKind::OneWay(direction) => ("gate", wall.far_cell(), direction),
+        };
+        text += &format!("{key}: {} {} {direction}\n", y + 1, x + 1);
+    }
+    text.push('\n');
+    for row in grid.tiles.rows() {
+        for tile in row {
+            let kind = tile.kind();
+            text.push(legend.iter().find(|(_, k)| *k == kind).map_or('?', |(s, _)| *s));
+        }
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+pub fn from_compact(text: &str) -> Result<Grid, GridError> {
+    let header = parse_header(text)?;
+    let mut legend = LEGEND.to_vec();
+    legend.extend(header.symbols);
+
+    let mut data = Vec::new();
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.contains(':') {
+            continue;
+        }
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(column, symbol)| match legend.iter().find(|(s, _)| *s == symbol) {
+                Some((_, kind)) => Ok(kind.to_tile()),
+                None => Err(GridError::UnknownSymbol {
+                    line: line_index,
+                    column,
+                    symbol,
+                }),
+            })
+            .collect::<Result<Vec<_>, GridError>>()?;
+        data.push(row);
+    }
+
+    Grid::try_from(VecGrid::new(
+        header.version,
+        header.steps.ok_or(GridError::MissingSteps)?,
+        header.rules,
+        header.walls,
+        data,
+    ))
+}
+
+// Version of a compact level, without reading the board
+pub fn stored_version(text: &str) -> Result<u32, GridError> {
+    Ok(parse_header(text)?.version)
+}
+
+struct Header {
+    version: u32,
+    steps: Option<usize>,
+    rules: Rules,
+    // Symbols declared by `color`, `bomb`, `ice`, `portal`, `anchored`, `horizontal` and `vertical` lines
+    symbols: Vec<(char, TileKind)>,
+    walls: Vec<Wall>,
+}
+
+fn parse_header(text: &str) -> Result<Header, GridError> {
+    let mut header = Header {
+        version: 0,
+        steps: None,
+        rules: Rules::default(),
+        symbols: Vec::new(),
+        walls: Vec::new(),
+    };
+    for (line_index, line) in text.lines().enumerate() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let bad_header = || GridError::BadHeader {
+            line: line_index,
+            text: line.trim().to_string(),
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match key.split_once(' ') {
+            None if key == "version" => header.version = value.parse().map_err(|_| bad_header())?,
+            None if key == "steps" => header.steps = Some(value.parse().map_err(|_| bad_header())?),
+            None if key == "min_group" => header.rules.min_group = value.parse().map_err(|_| bad_header())?,
+            None if key == "connectivity" => header.rules.connectivity = value.parse().map_err(|_| bad_header())?,
+            None if key == "blockers" => header.rules.blockers = value.parse().map_err(|_| bad_header())?,
+            None if key == "movement" => header.rules.movement = value.parse().map_err(|_| bad_header())?,
+            None if key == "cascade" => header.rules.cascade = value.parse().map_err(|_| bad_header())?,
+            None if key == "wrap" => header.rules.wrap = value.parse().map_err(|_| bad_header())?,
+            None if key == "wall" || key == "gate" => {
+                let wall = parse_wall(key, value).ok_or_else(bad_header)?;
+                header.walls.push(wall);
+            }
+            Some((kind @ ("color" | "bomb" | "ice" | "portal" | "anchored" | "horizontal" | "vertical"), symbol)) => {
+                let mut chars = symbol.trim().chars();
+                let (Some(symbol), None) = (chars.next(), chars.next()) else {
+                    return Err(bad_header());
+                };
+                if LEGEND.iter().chain(&header.symbols).any(|(s, _)| *s == symbol) {
+                    return Err(bad_header());
+                }
+                let kind = match kind {
+                    "portal" => TileKind::Portal(value.parse().map_err(|_| bad_header())?),
+                    _ => {
+                        let color = value.parse().map_err(|_| bad_header())?;
+                        match kind {
+                            "bomb" => TileKind::Bomb(color),
+                            "ice" => TileKind::Ice(color),
+                            "anchored" => TileKind::Regular(color, Moves::Anchored),
+                            "horizontal" => TileKind::Regular(color, Moves::Horizontal),
+                            "vertical" => TileKind::Regular(color, Moves::Vertical),
+                            _ => TileKind::Regular(color, Moves::Any),
+                        }
+                    }
+                };
+                header.symbols.push((symbol, kind));
+            }
+            _ => return Err(bad_header()),
+        }
+    }
+    Ok(header)
+}
+
+// `<row> <column> <direction>`, 1-based, for either a `wall` or a `gate` line
+fn parse_wall(key: &str, value: &str) -> Option<Wall> {
+    let mut parts = value.split_whitespace();
+    let (Some(row), Some(column), Some(direction), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    let at = (
+        row.parse::<usize>().ok()?.checked_sub(1)?,
+        column.parse::<usize>().ok()?.checked_sub(1)?,
+    );
+    let direction = direction.parse().ok()?;
+    let kind = match key {
+        "gate" => WallKind::OneWay(direction),
+        _ => WallKind::Solid,
+    };
+    Wall::on_edge(at, direction, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::grid::{
+        FORMAT_VERSION,
+        rules::{Blockers, Connectivity, Movement},
+        tests::error,
+        wall::Side,
+    };
+
+    // Every kind of header line and every symbol of the default legend
+    fn sample() -> String {
+        format!(
+            "version: {FORMAT_VERSION}
+steps: 7
+min_group: 3
+connectivity: 8
+blockers: count
+movement: slide
+cascade: true
+wrap: true
+color a: LightRed
+bomb b: Red
+ice c: Blue
+portal d: 1
+anchored e: Green
+horizontal f: Yellow
+vertical g: Magenta
+wall: 1 1 right
+gate: 2 3 up
+
+abcd~
+efgd.
+R#*+G
+"
+        )
+    }
+
+    #[test]
+    fn reads_header_and_board() {
+        let grid = from_compact(&sample()).unwrap();
+        assert_eq!((grid.get_height(), grid.get_width()), (3, 5));
+        assert_eq!(grid.steps, 7);
+        let rules = Rules {
+            min_group: 3,
+            connectivity: Connectivity::Eight,
+            blockers: Blockers::Count,
+            movement: Movement::Slide,
+            cascade: true,
+            wrap: true,
+        };
+        assert_eq!(grid.rules, rules);
+
+        let kinds: Vec<TileKind> = grid.tiles.iter().map(|tile| tile.kind()).collect();
+        assert!(
+            kinds[..5]
+                == [
+                    TileKind::Regular(Color::LightRed, Moves::Any),
+                    TileKind::Bomb(Color::Red),
+                    TileKind::Ice(Color::Blue),
+                    TileKind::Portal(1),
+                    TileKind::Void,
+                ]
+        );
+        assert!(
+            kinds[5..8]
+                == [
+                    TileKind::Regular(Color::Green, Moves::Anchored),
+                    TileKind::Regular(Color::Yellow, Moves::Horizontal),
+                    TileKind::Regular(Color::Magenta, Moves::Vertical),
+                ]
+        );
+        assert!(
+            kinds[10..]
+                == [
+                    TileKind::Regular(Color::Red, Moves::Any),
+                    TileKind::Blocker,
+                    TileKind::Wildcard,
+                    TileKind::Crate,
+                    TileKind::Regular(Color::Green, Moves::Any),
+                ]
+        );
+
+        let walls = [
+            Wall {
+                at: (0, 0),
+                side: Side::Right,
+                kind: WallKind::Solid,
+            },
+            // A gate crossed going up sits below the cell above
+            Wall {
+                at: (0, 2),
+                side: Side::Below,
+                kind: WallKind::OneWay(MoveDir::Up),
+            },
+        ];
+        assert_eq!(grid.walls, walls);
+    }
+
+    #[test]
+    fn round_trips() {
+        let grid = from_compact(&sample()).unwrap();
+        let text = to_compact(&grid).unwrap();
+        let reread = from_compact(&text).unwrap();
+        assert_eq!(reread.fingerprint(), grid.fingerprint(), "{text}");
+        assert_eq!(reread.steps, grid.steps);
+        assert_eq!(reread.rules, grid.rules);
+        assert_eq!(reread.walls, grid.walls);
+        // Writing is deterministic, so saving an unchanged level doesn't touch the file
+        assert_eq!(to_compact(&reread).unwrap(), text);
+    }
+
+    #[test]
+    fn classic_level_writes_no_extra_header() {
+        let grid = from_compact("steps: 2\n\nRG\n#.\n").unwrap();
+        assert_eq!(to_compact(&grid).unwrap(), "version: 1\nsteps: 2\n\nRG\n#.\n");
+    }
+
+    #[test]
+    fn rejects_bad_levels() {
+        assert!(matches!(
+            error(from_compact("steps: 1\n\nRX\n")),
+            GridError::UnknownSymbol {
+                line: 2,
+                column: 1,
+                symbol: 'X'
+            }
+        ));
+        assert!(matches!(error(from_compact("\nR\n")), GridError::MissingSteps));
+        assert!(matches!(
+            error(from_compact("steps: 1\nspeed: 3\n\nR\n")),
+            GridError::BadHeader { line: 1, .. }
+        ));
+        assert!(matches!(
+            error(from_compact("steps: lots\n\nR\n")),
+            GridError::BadHeader { line: 0, .. }
+        ));
+        // Declarations can't take over a symbol of the default legend or one declared before
+        assert!(matches!(
+            error(from_compact("steps: 1\ncolor R: Blue\n\nR\n")),
+            GridError::BadHeader { line: 1, .. }
+        ));
+        assert!(matches!(
+            error(from_compact("steps: 1\ncolor a: Blue\nbomb a: Red\n\na\n")),
+            GridError::BadHeader { line: 2, .. }
+        ));
+        assert!(matches!(
+            error(from_compact("steps: 1\nwall: 1 2 right\n\nRG\n")),
+            GridError::BadWall { .. }
+        ));
+        assert!(matches!(
+            error(from_compact("steps: 1\nportal a: 1\n\naR\n")),
+            GridError::UnpairedPortal { pair: 1, count: 1 }
+        ));
+        assert!(matches!(
+            error(from_compact("steps: 1\n\nRG\nR\n")),
+            GridError::RaggedRow { row: 1, .. }
+        ));
+        assert!(matches!(
+            error(from_compact("steps: 1\nmin_group: 1\n\nR\n")),
+            GridError::MinGroupTooSmall { min_group: 1 }
+        ));
+    }
+
+    #[test]
+    fn too_many_kinds_are_refused() {
+        let mut grid = Grid::new(1, 40, 5);
+        for (x, tile) in grid.tiles.iter_mut().enumerate() {
+            *tile = TileKind::Regular(Color::Indexed(16 + x as u8), Moves::Any).to_tile();
+        }
+        assert!(matches!(
+            to_compact(&grid),
+            Err(GridError::TooManyKinds { count: 40, symbols: 36 })
+        ));
+        // As many as there are symbols still fit
+        grid.resize(1, 36);
+        assert_eq!(
+            from_compact(&to_compact(&grid).unwrap()).unwrap().fingerprint(),
+            grid.fingerprint()
+        );
+    }
+}
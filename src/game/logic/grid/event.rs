@@ -0,0 +1,38 @@
+use super::{MoveDir, tile::Tile};
+
+#[derive(Clone, Copy)]
+pub enum GridEvent {
+    Slide {
+        tile: Tile,
+        from: (usize, usize),
+        to: (usize, usize),
+        direction: MoveDir,
+        // The portal the tile came out of, if it went through one on the way
+        through: Option<(usize, usize)>,
+    },
+    Clear {
+        tile: Tile,
+        at: (usize, usize),
+        // 1 for groups formed by the move itself, then counting up through each cascade
+        wave: usize,
+        // Which of the groups cleared in this wave the tile belonged to, counting from 0
+        group: usize,
+    },
+    // Ice in a clearing group, left behind as a regular tile of its color
+    Crack {
+        tile: Tile,
+        at: (usize, usize),
+        wave: usize,
+        group: usize,
+    },
+}
+
+impl GridEvent {
+    // (wave, group) of tiles cleared or cracked by a group
+    pub fn cleared_in(&self) -> Option<(usize, usize)> {
+        match self {
+            GridEvent::Clear { wave, group, .. } | GridEvent::Crack { wave, group, .. } => Some((*wave, *group)),
+            GridEvent::Slide { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+// How groups form and when they clear. Missing fields in a level file fall back to the classic rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    // Fewest tiles a group needs before it clears, at least 2
+    pub min_group: usize,
+    pub connectivity: Connectivity,
+    pub blockers: Blockers,
+    pub movement: Movement,
+    // After a clear, the rest of the board settles in the move's direction and can clear again
+    pub cascade: bool,
+    // Edges join up with the opposite edge, for moving and for grouping
+    pub wrap: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Connectivity {
+    // Sides only
+    Four,
+    // Sides and corners
+    Eight,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Blockers {
+    // Blockers never join a group and never clear
+    Separate,
+    // A blocker counts toward the size of every group it touches and clears along with any of them
+    Count,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Movement {
+    // Every tile moves one cell per move
+    Step,
+    // Tiles keep going until they hit the edge, a blocker or another tile
+    Slide,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            min_group: 4,
+            connectivity: Connectivity::Four,
+            blockers: Blockers::Separate,
+            movement: Movement::Step,
+            cascade: false,
+            wrap: false,
+        }
+    }
+}
+
+impl Rules {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Connectivity {
+    // (dy, dx) of every neighbour of a cell
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            Connectivity::Eight => &[(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (-1, 1), (1, -1), (1, 1)],
+        }
+    }
+}
+
+// e.g. "groups of 4+ · 4-way", followed by any of " · blockers count", " · tiles slide", " · cascades"
+// and " · edges wrap"
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "groups of {}+ · {}-way", self.min_group, self.connectivity)?;
+        if self.blockers == Blockers::Count {
+            write!(f, " · blockers count")?;
+        }
+        if self.movement == Movement::Slide {
+            write!(f, " · tiles slide")?;
+        }
+        if self.cascade {
+            write!(f, " · cascades")?;
+        }
+        if self.wrap {
+            write!(f, " · edges wrap")?;
+        }
+        Ok(())
+    }
+}
+
+// The compact format spells these as "4" / "8", "separate" / "count" and "step" / "slide"
+impl fmt::Display for Connectivity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Connectivity::Four => write!(f, "4"),
+            Connectivity::Eight => write!(f, "8"),
+        }
+    }
+}
+
+impl FromStr for Connectivity {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(Connectivity::Four),
+            "8" => Ok(Connectivity::Eight),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Blockers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Blockers::Separate => write!(f, "separate"),
+            Blockers::Count => write!(f, "count"),
+        }
+    }
+}
+
+impl FromStr for Blockers {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "separate" => Ok(Blockers::Separate),
+            "count" => Ok(Blockers::Count),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Movement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Movement::Step => write!(f, "step"),
+            Movement::Slide => write!(f, "slide"),
+        }
+    }
+}
+
+impl FromStr for Movement {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "step" => Ok(Movement::Step),
+            "slide" => Ok(Movement::Slide),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,481 @@
+use ratatui::style::Color;
+
+use super::{
+    Grid, MoveDir,
+    rules::{Blockers, Connectivity, Movement, Rules},
+    tile::{Moves, PALETTE, TileKind},
+    validation::GridError,
+    vec_grid::{FORMAT_VERSION, VecGrid},
+    wall::{Side, Wall, WallKind},
+};
+
+// Layout before base64url encoding:
+//   code version, height, width, steps (LEB128), min group size, rule flags, extra tile count,
+//   extra tiles, wall count, walls, two tiles per byte, then a 16-bit checksum of everything before it.
+// Tile nibbles: 0 empty, 1 blocker, 2..=7 palette colors, 8..=15 extra tiles.
+// Extra tiles are a kind byte followed by a length prefixed color name, or a pair number for portals.
+// Walls are the row and column of the cell above or left of them, then a byte of wall flags.
+// Version 1 codes have no rule bytes and play by the default rules. Up to version 2, extra tiles were
+// always regular and only their color name was written. Walls came with version 4. Kinds of extra tile
+// added since share that version, since a build that doesn't know a kind byte already turns the code down.
+const CODE_VERSION: u8 = 4;
+const EIGHT_WAY: u8 = 1 << 0;
+const BLOCKERS_COUNT: u8 = 1 << 1;
+const SLIDE: u8 = 1 << 2;
+const CASCADE: u8 = 1 << 3;
+const WRAP: u8 = 1 << 4;
+const KNOWN_FLAGS: u8 = EIGHT_WAY | BLOCKERS_COUNT | SLIDE | CASCADE | WRAP;
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const MAX_EXTRA_TILES: usize = 8;
+const REGULAR: u8 = 0;
+const WILDCARD: u8 = 1;
+const BOMB: u8 = 2;
+const ICE: u8 = 3;
+const PORTAL: u8 = 4;
+const CRATE: u8 = 5;
+const ANCHORED: u8 = 6;
+const HORIZONTAL: u8 = 7;
+const VERTICAL: u8 = 8;
+const VOID: u8 = 9;
+// Wall flags
+const BELOW: u8 = 1 << 0;
+// Gates crossed moving right or down
+const GATE_FORWARD: u8 = 1 << 1;
+// Gates crossed moving left or up
+const GATE_BACK: u8 = 1 << 2;
+
+pub fn to_share_code(grid: &Grid) -> Result<String, GridError> {
+    let (height, width) = (grid.get_height(), grid.get_width());
+    let (Ok(height), Ok(width)) = (u8::try_from(height), u8::try_from(width)) else {
+        return Err(GridError::InvalidCode(
+            "boards over 255 tiles wide or high can't be shared",
+        ));
+    };
+
+    let mut extra_tiles: Vec<TileKind> = Vec::new();
+    let mut nibbles = Vec::with_capacity(grid.tiles.len());
+    for kind in grid.tiles.iter().map(|tile| tile.kind()) {
+        let palette_index = match kind {
+            TileKind::Regular(color, Moves::Any) => PALETTE.iter().position(|c| *c == color),
+            _ => None,
+        };
+        nibbles.push(match (kind, palette_index) {
+            (TileKind::Empty, _) => 0,
+            (TileKind::Blocker, _) => 1,
+            (_, Some(index)) => index as u8 + 2,
+            _ => {
+                let index = extra_tiles.iter().position(|k| *k == kind).unwrap_or_else(|| {
+                    extra_tiles.push(kind);
+                    extra_tiles.len() - 1
+                });
+                if index >= MAX_EXTRA_TILES {
+                    return Err(GridError::InvalidCode(
+                        "too many special tiles and colors outside the palette to share",
+                    ));
+                }
+                index as u8 + 8
+            }
+        });
+    }
+
+    let Ok(min_group) = u8::try_from(grid.rules.min_group) else {
+        return Err(GridError::InvalidCode("groups over 255 tiles can't be shared"));
+    };
+    let mut flags = 0;
+    if grid.rules.connectivity == Connectivity::Eight {
+        flags |= EIGHT_WAY;
+    }
+    if grid.rules.blockers == Blockers::Count {
+        flags |= BLOCKERS_COUNT;
+    }
+    if grid.rules.movement == Movement::Slide {
+        flags |= SLIDE;
+    }
+    if grid.rules.cascade {
+        flags |= CASCADE;
+    }
+    if grid.rules.wrap {
+        flags |= WRAP;
+    }
+
+    let mut bytes = vec![CODE_VERSION, height, width];
+    let mut steps = grid.steps;
+    loop {
+        let byte = (steps & 0x7f) as u8;
+        steps >>= 7;
+        if steps == 0 {
+            bytes.push(byte);
+            break;
+        }
+        bytes.push(byte | 0x80);
+    }
+    bytes.extend([min_group, flags]);
+    bytes.push(extra_tiles.len() as u8);
+    for kind in extra_tiles {
+        let (kind_byte, color) = match kind {
+            TileKind::Regular(color, Moves::Any) => (REGULAR, Some(color)),
+            TileKind::Regular(color, Moves::Anchored) => (ANCHORED, Some(color)),
+            TileKind::Regular(color, Moves::Horizontal) => (HORIZONTAL, Some(color)),
+            TileKind::Regular(color, Moves::Vertical) => (VERTICAL, Some(color)),
+            TileKind::Wildcard => (WILDCARD, None),
+            TileKind::Crate => (CRATE, None),
+            TileKind::Void => (VOID, None),
+            TileKind::Bomb(color) => (BOMB, Some(color)),
+            TileKind::Ice(color) => (ICE, Some(color)),
+            TileKind::Portal(pair) => {
+                bytes.extend([PORTAL, pair]);
+                continue;
+            }
+            // Always written as nibbles 0 and 1
+            TileKind::Empty | TileKind::Blocker => continue,
+        };
+        bytes.push(kind_byte);
+        if let Some(color) = color {
+            let name = color.to_string();
+            bytes.push(name.len() as u8);
+            bytes.extend(name.as_bytes());
+        }
+    }
+    let Ok(wall_count) = u8::try_from(grid.walls.len()) else {
+        return Err(GridError::InvalidCode("boards with over 255 walls can't be shared"));
+    };
+    bytes.push(wall_count);
+    for wall in &grid.walls {
+        // Fits, the board being no larger than 255 cells either way
+        bytes.extend([wall.at.0 as u8, wall.at.1 as u8]);
+        let mut flags = match wall.side {
+            Side::Right => 0,
+            Side::Below => BELOW,
+        };
+        flags |= match wall.kind {
+            WallKind::Solid => 0,
+            WallKind::OneWay(MoveDir::Right | MoveDir::Down) => GATE_FORWARD,
+            WallKind::OneWay(MoveDir::Left | MoveDir::Up) => GATE_BACK,
+        };
+        bytes.push(flags);
+    }
+    bytes.extend(
+        nibbles
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0)),
+    );
+    bytes.extend(checksum(&bytes).to_be_bytes());
+
+    Ok(encode(&bytes))
+}
+
+pub fn from_share_code(code: &str) -> Result<Grid, GridError> {
+    let bytes = decode(code.trim())?;
+    let Some((payload, sum)) = bytes.split_last_chunk::<2>() else {
+        return Err(GridError::InvalidCode("code is too short"));
+    };
+    if checksum(payload) != u16::from_be_bytes(*sum) {
+        return Err(GridError::InvalidCode(
+            "checksum does not match, the code may be mistyped",
+        ));
+    }
+
+    let mut reader = payload.iter().copied();
+    let mut next = || reader.next().ok_or(GridError::InvalidCode("code is cut short"));
+    let version = next()?;
+    if version > CODE_VERSION {
+        return Err(GridError::InvalidCode("code was made by a newer version of the game"));
+    }
+    let (height, width) = (next()? as usize, next()? as usize);
+    let mut steps: usize = 0;
+    for shift in (0..usize::BITS).step_by(7) {
+        let byte = next()?;
+        steps |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    let mut rules = Rules::default();
+    if version >= 2 {
+        rules.min_group = next()? as usize;
+        let flags = next()?;
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(GridError::InvalidCode(
+                "code uses rules this version of the game doesn't know",
+            ));
+        }
+        if flags & EIGHT_WAY != 0 {
+            rules.connectivity = Connectivity::Eight;
+        }
+        if flags & BLOCKERS_COUNT != 0 {
+            rules.blockers = Blockers::Count;
+        }
+        if flags & SLIDE != 0 {
+            rules.movement = Movement::Slide;
+        }
+        rules.cascade = flags & CASCADE != 0;
+        rules.wrap = flags & WRAP != 0;
+    }
+    let mut extra_tiles = Vec::new();
+    for _ in 0..next()? {
+        let kind_byte = if version >= 3 { next()? } else { REGULAR };
+        let mut color = || -> Result<Color, GridError> {
+            let name: Vec<u8> = (0..next()?).map(|_| next()).collect::<Result<_, _>>()?;
+            let color = String::from_utf8(name).ok().and_then(|name| name.parse().ok());
+            color.ok_or(GridError::InvalidCode("code contains an unknown color"))
+        };
+        extra_tiles.push(match kind_byte {
+            REGULAR => TileKind::Regular(color()?, Moves::Any),
+            ANCHORED => TileKind::Regular(color()?, Moves::Anchored),
+            HORIZONTAL => TileKind::Regular(color()?, Moves::Horizontal),
+            VERTICAL => TileKind::Regular(color()?, Moves::Vertical),
+            WILDCARD => TileKind::Wildcard,
+            BOMB => TileKind::Bomb(color()?),
+            ICE => TileKind::Ice(color()?),
+            PORTAL => TileKind::Portal(next()?),
+            CRATE => TileKind::Crate,
+            VOID => TileKind::Void,
+            _ => {
+                return Err(GridError::InvalidCode(
+                    "code contains a tile this version of the game doesn't know",
+                ));
+            }
+        });
+    }
+    let mut walls = Vec::new();
+    for _ in 0..if version >= 4 { next()? } else { 0 } {
+        let at = (next()? as usize, next()? as usize);
+        let flags = next()?;
+        let (side, forward, back) = match flags & BELOW {
+            0 => (Side::Right, MoveDir::Right, MoveDir::Left),
+            _ => (Side::Below, MoveDir::Down, MoveDir::Up),
+        };
+        let kind = match flags & !BELOW {
+            0 => WallKind::Solid,
+            GATE_FORWARD => WallKind::OneWay(forward),
+            GATE_BACK => WallKind::OneWay(back),
+            _ => {
+                return Err(GridError::InvalidCode(
+                    "code contains a wall this version of the game doesn't know",
+                ));
+            }
+        };
+        walls.push(Wall { at, side, kind });
+    }
+
+    let mut nibbles = Vec::with_capacity(height * width + 1);
+    for _ in 0..(height * width).div_ceil(2) {
+        let byte = next()?;
+        nibbles.extend([byte >> 4, byte & 0x0f]);
+    }
+    if next().is_ok() {
+        return Err(GridError::InvalidCode("code has data after the last tile"));
+    }
+
+    let tile = |nibble: u8| {
+        let kind = match nibble {
+            0 => Some(TileKind::Empty),
+            1 => Some(TileKind::Blocker),
+            2..=7 => Some(TileKind::Regular(PALETTE[nibble as usize - 2], Moves::Any)),
+            _ => extra_tiles.get(nibble as usize - 8).copied(),
+        };
+        kind.map(TileKind::to_tile)
+            .ok_or(GridError::InvalidCode("code refers to a tile it does not define"))
+    };
+    let data = match width {
+        0 => Vec::new(),
+        _ => nibbles[..height * width]
+            .chunks(width)
+            .map(|row| row.iter().map(|&nibble| tile(nibble)).collect())
+            .collect::<Result<_, _>>()?,
+    };
+    Grid::try_from(VecGrid::new(FORMAT_VERSION, steps, rules, walls, data))
+}
+
+// Folded FNV-1a, enough to catch a mistyped or truncated code
+fn checksum(bytes: &[u8]) -> u16 {
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in bytes {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    (hash >> 16) as u16 ^ hash as u16
+}
+
+// Unpadded base64url
+fn encode(bytes: &[u8]) -> String {
+    let mut code = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (i, byte)| group | (*byte as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            code.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    code
+}
+
+fn decode(code: &str) -> Result<Vec<u8>, GridError> {
+    let values = code
+        .bytes()
+        .map(|byte| ALPHABET.iter().position(|&c| c == byte).map(|value| value as u32))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or(GridError::InvalidCode(
+            "code contains characters that are not part of a level code",
+        ))?;
+    if values.len() % 4 == 1 {
+        return Err(GridError::InvalidCode("code has the wrong length"));
+    }
+
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |group, (i, value)| group | value << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            bytes.push((group >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::grid::tests::{assert_same_level, error, level};
+
+    fn assert_round_trips(grid: &Grid) {
+        let code = to_share_code(grid).unwrap();
+        assert_same_level(&from_share_code(&code).unwrap(), grid, &code);
+    }
+
+    // A code for hand-made bytes, so decoding errors past the checksum can be reached
+    fn seal(payload: &[u8]) -> String {
+        let mut bytes = payload.to_vec();
+        bytes.extend(checksum(payload).to_be_bytes());
+        encode(&bytes)
+    }
+
+    #[test]
+    fn base64_round_trips_every_length() {
+        let bytes: Vec<u8> = (0..=255).rev().collect();
+        for len in 0..10 {
+            let code = encode(&bytes[..len]);
+            assert_eq!(code.len(), (len * 4).div_ceil(3));
+            assert_eq!(decode(&code).unwrap(), &bytes[..len]);
+        }
+    }
+
+    #[test]
+    fn round_trips_odd_tile_counts() {
+        assert_round_trips(&level("steps: 3\n\nR\n"));
+        assert_round_trips(&level("steps: 3\n\nRGB\n#.Y\nMCR\n"));
+    }
+
+    #[test]
+    fn round_trips_steps_past_one_byte() {
+        for steps in [0, 127, 128, 300, 16_383, 16_384, 999_999] {
+            assert_round_trips(&level(&format!("steps: {steps}\n\nRG\n")));
+        }
+    }
+
+    #[test]
+    fn round_trips_rules_special_tiles_and_walls() {
+        // Codes have room for eight extra tile kinds, so the special tiles are split over two levels
+        assert_round_trips(&level(
+            "steps: 9
+min_group: 3
+connectivity: 8
+blockers: count
+movement: slide
+cascade: true
+wrap: true
+color a: LightRed
+bomb b: Red
+ice c: Blue
+portal d: 2
+anchored e: Green
+horizontal f: Yellow
+vertical g: Magenta
+wall: 1 1 right
+wall: 1 2 down
+gate: 2 3 up
+gate: 3 1 right
+
+abcd~
+efgd.
+R#.~G
+",
+        ));
+        assert_round_trips(&level("steps: 2\nwall: 2 1 right\n\n*+~\nR.G\n"));
+    }
+
+    #[test]
+    fn reads_version_1_codes() {
+        // Version 1 had no rule bytes, no extra tile kinds and no walls
+        let grid = from_share_code(&seal(&[
+            1, 1, 2, 0xc8, 0x01, 1, 8, b'D', b'a', b'r', b'k', b'G', b'r', b'a', b'y', 0x28,
+        ]))
+        .unwrap();
+        assert_eq!(grid.steps, 200);
+        assert!(grid.rules.is_default());
+        assert!(grid.tiles[[0, 0]].kind() == TileKind::Regular(PALETTE[0], Moves::Any));
+        assert!(grid.tiles[[0, 1]].kind() == TileKind::Regular(Color::DarkGray, Moves::Any));
+    }
+
+    #[test]
+    fn rejects_damaged_codes() {
+        let code = to_share_code(&level("steps: 300\nwall: 1 1 right\n\n~RGB\nR.#*\n")).unwrap();
+
+        let mut mistyped = code.clone().into_bytes();
+        mistyped[4] = if mistyped[4] == b'A' { b'B' } else { b'A' };
+        assert!(
+            error(from_share_code(&String::from_utf8(mistyped).unwrap()))
+                .to_string()
+                .contains("checksum")
+        );
+
+        for len in 0..code.len() {
+            assert!(from_share_code(&code[..len]).is_err(), "{}", &code[..len]);
+        }
+        assert!(error(from_share_code("AB!D")).to_string().contains("characters"));
+        assert!(error(from_share_code("ABCDE")).to_string().contains("length"));
+    }
+
+    #[test]
+    fn rejects_codes_that_check_out_but_do_not_make_sense() {
+        let header = [CODE_VERSION, 1, 2, 3, 4];
+        let finish = |rest: &[u8]| seal(&[&header[..], rest].concat());
+
+        // Cut short after the flags, with a checksum that still matches
+        assert!(error(from_share_code(&finish(&[0]))).to_string().contains("cut short"));
+        assert!(
+            error(from_share_code(&finish(&[1 << 7, 0, 0, 0x23])))
+                .to_string()
+                .contains("rules")
+        );
+        assert!(
+            error(from_share_code(&finish(&[0, 1, 200, 0, 0x28])))
+                .to_string()
+                .contains("tile this version")
+        );
+        assert!(
+            error(from_share_code(&finish(&[0, 0, 1, 0, 0, 1 << 3, 0x23])))
+                .to_string()
+                .contains("wall this version")
+        );
+        assert!(
+            error(from_share_code(&finish(&[0, 0, 0, 0x28])))
+                .to_string()
+                .contains("does not define")
+        );
+        assert!(
+            error(from_share_code(&finish(&[0, 0, 0, 0x23, 0])))
+                .to_string()
+                .contains("after the last tile")
+        );
+        assert!(
+            error(from_share_code(&seal(&[CODE_VERSION + 1, 1, 2, 3, 4, 0, 0, 0, 0x23])))
+                .to_string()
+                .contains("newer version")
+        );
+    }
+}
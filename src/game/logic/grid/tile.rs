@@ -1,7 +1,19 @@
 use ratatui::style::Color;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, de::Error};
 use uid::Id;
 
+use super::MoveDir;
+
+// Colors offered by the level editor and expected in level files
+pub const PALETTE: [Color; 6] = [
+    Color::Red,
+    Color::Green,
+    Color::Blue,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Cyan,
+];
+
 #[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum Tile {
     Empty,
@@ -9,6 +21,140 @@ pub enum Tile {
     Regular {
         #[serde(skip)]
         id: Id<Tile>,
+        #[serde(deserialize_with = "deserialize_color")]
+        color: Color,
+        #[serde(default, skip_serializing_if = "Moves::is_any")]
+        moves: Moves,
+    },
+    // Joins any group it touches, whatever the color. Never has to be cleared to win.
+    Wildcard,
+    // Clears everything around it, corners included, when its group clears
+    Bomb {
+        #[serde(skip)]
+        id: Id<Tile>,
+        #[serde(deserialize_with = "deserialize_color")]
+        color: Color,
+    },
+    // Takes two clears: the first cracks it into a regular tile of the same color
+    Ice {
+        #[serde(skip)]
+        id: Id<Tile>,
+        #[serde(deserialize_with = "deserialize_color")]
         color: Color,
     },
+    // Fixed in place. A tile moving into it comes out of the other portal with the same pair number.
+    Portal {
+        pair: u8,
+    },
+    // A blocker that slides with the board. Never joins a group and never clears.
+    Crate,
+    // Not part of the board at all, for boards that aren't rectangles. Nothing enters it and it isn't drawn.
+    Void,
+}
+
+// Which ways a regular tile goes along with the board
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Moves {
+    #[default]
+    Any,
+    // Never moves, though it still groups and clears
+    Anchored,
+    // Only left and right
+    Horizontal,
+    // Only up and down
+    Vertical,
+}
+
+// A tile without its id, for comparing boards and writing them out
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TileKind {
+    Empty,
+    Blocker,
+    Regular(Color, Moves),
+    Wildcard,
+    Bomb(Color),
+    Ice(Color),
+    Portal(u8),
+    Crate,
+    Void,
+}
+
+impl Tile {
+    pub fn kind(&self) -> TileKind {
+        match *self {
+            Tile::Empty => TileKind::Empty,
+            Tile::Blocker => TileKind::Blocker,
+            Tile::Regular { color, moves, .. } => TileKind::Regular(color, moves),
+            Tile::Wildcard => TileKind::Wildcard,
+            Tile::Bomb { color, .. } => TileKind::Bomb(color),
+            Tile::Ice { color, .. } => TileKind::Ice(color),
+            Tile::Portal { pair } => TileKind::Portal(pair),
+            Tile::Crate => TileKind::Crate,
+            Tile::Void => TileKind::Void,
+        }
+    }
+
+    // The color of tiles that belong to a group of that color
+    pub fn color(&self) -> Option<Color> {
+        match *self {
+            Tile::Regular { color, .. } | Tile::Bomb { color, .. } | Tile::Ice { color, .. } => Some(color),
+            _ => None,
+        }
+    }
+
+    // Whether the tile slides when the board moves in `direction`
+    pub fn can_move(&self, direction: MoveDir) -> bool {
+        match self {
+            Tile::Regular { moves, .. } => moves.allows(direction),
+            Tile::Wildcard | Tile::Bomb { .. } | Tile::Ice { .. } | Tile::Crate => true,
+            Tile::Empty | Tile::Blocker | Tile::Portal { .. } | Tile::Void => false,
+        }
+    }
+}
+
+impl TileKind {
+    pub fn to_tile(self) -> Tile {
+        match self {
+            TileKind::Empty => Tile::Empty,
+            TileKind::Blocker => Tile::Blocker,
+            TileKind::Regular(color, moves) => Tile::Regular {
+                id: Id::new(),
+                color,
+                moves,
+            },
+            TileKind::Wildcard => Tile::Wildcard,
+            TileKind::Bomb(color) => Tile::Bomb { id: Id::new(), color },
+            TileKind::Ice(color) => Tile::Ice { id: Id::new(), color },
+            TileKind::Portal(pair) => Tile::Portal { pair },
+            TileKind::Crate => Tile::Crate,
+            TileKind::Void => Tile::Void,
+        }
+    }
+}
+
+impl Moves {
+    pub fn is_any(&self) -> bool {
+        *self == Moves::Any
+    }
+
+    pub fn allows(self, direction: MoveDir) -> bool {
+        match self {
+            Moves::Any => true,
+            Moves::Anchored => false,
+            Moves::Horizontal => matches!(direction, MoveDir::Left | MoveDir::Right),
+            Moves::Vertical => matches!(direction, MoveDir::Up | MoveDir::Down),
+        }
+    }
+}
+
+// Same names ratatui accepts, with an error that lists the palette instead of a generic parse failure
+fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse().map_err(|_| {
+        let palette: Vec<String> = PALETTE.iter().map(Color::to_string).collect();
+        D::Error::custom(format!(
+            "unknown color \"{name}\", expected one of {}",
+            palette.join(", ")
+        ))
+    })
 }
@@ -0,0 +1,100 @@
+use std::fmt;
+
+use ratatui::style::Color;
+
+use super::FORMAT_VERSION;
+
+// Problems that keep a level file from loading at all. Rows and columns are 1-based in messages.
+#[derive(Debug)]
+pub enum GridError {
+    NewerVersion { version: u32 },
+    NoCells { height: usize, width: usize },
+    RowCount { height: usize, rows: usize },
+    RaggedRow { row: usize, width: usize, tiles: usize },
+    // Every portal needs exactly one partner with the same pair number
+    UnpairedPortal { pair: u8, count: usize },
+    BadWall { at: (usize, usize), reason: &'static str },
+    // A single tile would count as a group and clear the moment it is placed
+    MinGroupTooSmall { min_group: usize },
+    // Compact format only
+    MissingSteps,
+    BadHeader { line: usize, text: String },
+    UnknownSymbol { line: usize, column: usize, symbol: char },
+    // More kinds of tile outside the default legend than there are symbols to declare them with
+    TooManyKinds { count: usize, symbols: usize },
+    // Share codes only
+    InvalidCode(&'static str),
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::NewerVersion { version } => write!(
+                f,
+                "level uses format version {version}, this build only reads up to version {FORMAT_VERSION}"
+            ),
+            GridError::NoCells { height, width } => {
+                write!(f, "board has no cells (height {height}, width {width})")
+            }
+            GridError::RowCount { height, rows } => {
+                write!(f, "height is {height} but data has {rows} rows")
+            }
+            GridError::RaggedRow { row, width, tiles } => {
+                write!(f, "row {} has {tiles} tiles but width is {width}", row + 1)
+            }
+            GridError::UnpairedPortal { pair, count } => {
+                write!(f, "portal pair {pair} has {count} portals, it needs exactly 2")
+            }
+            GridError::BadWall { at: (y, x), reason } => {
+                write!(f, "wall at row {}, column {}: {reason}", y + 1, x + 1)
+            }
+            GridError::MinGroupTooSmall { min_group } => {
+                write!(f, "min_group is {min_group}, groups need at least 2 tiles")
+            }
+            GridError::MissingSteps => write!(f, "missing a `steps: <number>` header line"),
+            GridError::BadHeader { line, text } => write!(
+                f,
+                "line {}: \"{text}\" is not `version: <number>`, `steps: <number>`, `min_group: <number>`, \
+                 `connectivity: 4|8`, `blockers: separate|count`, `movement: step|slide`, \
+                 `cascade: true|false`, `wrap: true|false`, `wall|gate: <row> <column> up|down|left|right` \
+                 or `color|bomb|ice|portal|anchored|horizontal|vertical <letter>: <color or pair>`",
+                line + 1
+            ),
+            GridError::UnknownSymbol { line, column, symbol } => {
+                write!(f, "line {}, column {}: unknown tile '{symbol}'", line + 1, column + 1)
+            }
+            GridError::TooManyKinds { count, symbols } => write!(
+                f,
+                "level has {count} kinds of tile outside the default legend, the compact format can only \
+                 declare {symbols}"
+            ),
+            GridError::InvalidCode(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+// Problems that still let the level load, but make it unwinnable or hard to read
+pub enum GridWarning {
+    NoSteps,
+    TooFewTiles { color: Color, count: usize, needed: usize },
+    OffPalette { color: Color, at: (usize, usize) },
+}
+
+impl fmt::Display for GridWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridWarning::NoSteps => write!(f, "steps is 0, so no move can be made"),
+            GridWarning::TooFewTiles { color, count, needed } => {
+                write!(
+                    f,
+                    "only {count} {color} tiles, at least {needed} are needed to clear them"
+                )
+            }
+            GridWarning::OffPalette { color, at: (y, x) } => {
+                write!(f, "{color} at row {}, column {} is not a palette color", y + 1, x + 1)
+            }
+        }
+    }
+}
@@ -1,14 +1,50 @@
+use std::collections::BTreeMap;
+
 use ndarray::Array2;
 use serde::{Deserialize, Serialize};
 
-use super::{Grid, tile::Tile};
+use super::{
+    Grid,
+    rules::Rules,
+    tile::{Moves, Tile, TileKind},
+    validation::GridError,
+    wall::Wall,
+};
+
+// Bump when the saved layout or the meaning of a field changes in a way older builds would misread, and
+// add a step to `migrate`. New fields that default to the old behaviour don't need a bump of their own.
+pub const FORMAT_VERSION: u32 = 2;
+// Levels with nothing newer than version 1 had are still written as version 1, so older builds open them
+const CLASSIC_VERSION: u32 = 1;
+
+// The oldest version that can hold `grid`, the one it is saved with
+pub fn format_version(grid: &Grid) -> u32 {
+    let classic_tiles = grid.tiles.iter().all(|tile| {
+        matches!(
+            tile.kind(),
+            TileKind::Empty | TileKind::Blocker | TileKind::Regular(_, Moves::Any)
+        )
+    });
+    match grid.rules.is_default() && grid.walls.is_empty() && classic_tiles {
+        true => CLASSIC_VERSION,
+        false => FORMAT_VERSION,
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct VecGrid {
+    // Levels written before the format was versioned have no version field and read as 0
+    #[serde(default)]
+    version: u32,
     steps: usize,
-    data: Vec<Vec<Tile>>,
+    // Only written when a level departs from the classic rules
+    #[serde(default, skip_serializing_if = "Rules::is_default")]
+    rules: Rules,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    walls: Vec<Wall>,
     height: usize,
     width: usize,
+    data: Vec<Vec<Tile>>,
 }
 
 impl From<Grid> for VecGrid {
@@ -16,22 +52,231 @@ impl From<Grid> for VecGrid {
         let array = grid.tiles.rows().into_iter().map(|chunk| chunk.to_vec()).collect();
 
         VecGrid {
+            version: format_version(&grid),
             steps: grid.steps,
-            data: array,
+            rules: grid.rules,
             width: grid.get_width(),
             height: grid.get_height(),
+            walls: grid.walls,
+            data: array,
+        }
+    }
+}
+
+impl VecGrid {
+    // Dimensions are taken from the data, so ragged rows are still caught when converting to a Grid
+    pub fn new(version: u32, steps: usize, rules: Rules, walls: Vec<Wall>, data: Vec<Vec<Tile>>) -> Self {
+        Self {
+            version,
+            steps,
+            rules,
+            walls,
+            height: data.len(),
+            width: data.first().map_or(0, Vec::len),
+            data,
+        }
+    }
+
+    // Brings an older level up to FORMAT_VERSION one version at a time
+    fn migrate(mut self) -> Result<Self, GridError> {
+        if self.version > FORMAT_VERSION {
+            return Err(GridError::NewerVersion { version: self.version });
+        }
+        if self.version == 0 {
+            // Version 1 only added the version field
+            self.version = 1;
         }
+        if self.version == 1 {
+            // Version 2 added rules, and every older level was made for the defaults. Everything added since
+            // also defaults to how older levels played, so it shares this version.
+            self.version = 2;
+        }
+        Ok(self)
     }
 }
 
-impl From<VecGrid> for Grid {
-    fn from(vec_grid: VecGrid) -> Self {
-        let vec_flat: Vec<Tile> = vec_grid.data.into_iter().flatten().collect();
-        Grid {
-            tiles: Array2::from_shape_vec((vec_grid.height, vec_grid.width), vec_flat).unwrap(),
-            steps: vec_grid.steps,
-            active_animations: Vec::new(),
-            pending_pop: false,
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    version: u32,
+}
+
+// Reads just the version of a serialized grid, without migrating or validating the rest
+pub fn stored_version(ron: &str) -> ron::error::SpannedResult<u32> {
+    ron::de::from_str::<VersionProbe>(ron).map(|probe| probe.version)
+}
+
+impl TryFrom<VecGrid> for Grid {
+    type Error = GridError;
+
+    fn try_from(vec_grid: VecGrid) -> Result<Self, Self::Error> {
+        let VecGrid {
+            version: _,
+            steps,
+            rules,
+            walls,
+            data,
+            height,
+            width,
+        } = vec_grid.migrate()?;
+
+        if height == 0 || width == 0 {
+            return Err(GridError::NoCells { height, width });
+        }
+        if rules.min_group < 2 {
+            return Err(GridError::MinGroupTooSmall {
+                min_group: rules.min_group,
+            });
+        }
+        if data.len() != height {
+            return Err(GridError::RowCount {
+                height,
+                rows: data.len(),
+            });
+        }
+        if let Some((row, tiles)) = data.iter().enumerate().find(|(_, tiles)| tiles.len() != width) {
+            return Err(GridError::RaggedRow {
+                row,
+                width,
+                tiles: tiles.len(),
+            });
+        }
+
+        let mut portals: BTreeMap<u8, usize> = BTreeMap::new();
+        for pair in data.iter().flatten().filter_map(|tile| match tile {
+            Tile::Portal { pair } => Some(*pair),
+            _ => None,
+        }) {
+            *portals.entry(pair).or_default() += 1;
+        }
+        if let Some((&pair, &count)) = portals.iter().find(|(_, count)| **count != 2) {
+            return Err(GridError::UnpairedPortal { pair, count });
         }
+
+        for (index, wall) in walls.iter().enumerate() {
+            let (y, x) = wall.far_cell();
+            let reason = if y >= height || x >= width {
+                "it is not between two cells of the board"
+            } else if !wall.is_aligned() {
+                "its gate points along the wall instead of through it"
+            } else if walls[..index]
+                .iter()
+                .any(|other| (other.at, other.side) == (wall.at, wall.side))
+            {
+                "another wall is already on that edge"
+            } else {
+                continue;
+            };
+            return Err(GridError::BadWall { at: wall.at, reason });
+        }
+
+        let vec_flat: Vec<Tile> = data.into_iter().flatten().collect();
+        Ok(Grid {
+            // The checks above guarantee the shape matches
+            tiles: Array2::from_shape_vec((height, width), vec_flat).unwrap(),
+            steps,
+            rules,
+            walls,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::{
+        grid::{LevelFormat, tests::assert_same_level},
+        level_pack::LevelPack,
+    };
+
+    const SAMPLE: &str = include_str!("../../../../grid.ron");
+    // grid.ron as it was before levels had a version field
+    const VERSION_0: &str = include_str!("../../../../tests/fixtures/version0.ron");
+
+    // Writing a level out in either format and reading it back must give the same board and step budget
+    fn assert_round_trips(grid: &Grid) {
+        for (name, format) in [("RON", LevelFormat::Ron), ("compact", LevelFormat::Compact)] {
+            let text = grid.to_text(format).unwrap();
+            let reread = Grid::from_text(&text).unwrap_or_else(|err| panic!("{name} didn't reload: {err}\n{text}"));
+            assert_same_level(&reread, grid, &text);
+        }
+    }
+
+    fn one_cell(version: u32) -> VecGrid {
+        VecGrid::new(version, 3, Rules::default(), Vec::new(), vec![vec![Tile::Empty]])
+    }
+
+    #[test]
+    fn sample_level_round_trips() {
+        assert_round_trips(&Grid::from_text(SAMPLE).unwrap());
+    }
+
+    #[test]
+    fn builtin_pack_round_trips() {
+        for level in &LevelPack::builtin().levels {
+            assert_round_trips(&level.grid);
+        }
+    }
+
+    #[test]
+    fn ron_is_detected_however_it_starts() {
+        let sample = Grid::from_text(SAMPLE).unwrap().fingerprint();
+        for text in [
+            format!("// Made by hand\n{SAMPLE}"),
+            format!("#![enable(implicit_some)]\n{SAMPLE}"),
+            format!("VecGrid{SAMPLE}"),
+        ] {
+            assert!(matches!(LevelFormat::detect(&text), LevelFormat::Ron), "{text}");
+            assert_eq!(Grid::from_text(&text).unwrap().fingerprint(), sample, "{text}");
+        }
+        assert!(matches!(LevelFormat::detect("steps: 3\n\nR\n"), LevelFormat::Compact));
+    }
+
+    #[test]
+    fn version_0_level_loads() {
+        assert_eq!(Grid::stored_version(VERSION_0, LevelFormat::Ron).unwrap(), 0);
+        let grid = Grid::from_text(VERSION_0).unwrap();
+        assert_eq!(grid.steps, 999999);
+        assert!(grid.rules.is_default());
+        // It is the same board as the versioned sample
+        assert_eq!(grid.fingerprint(), Grid::from_text(SAMPLE).unwrap().fingerprint());
+        assert_round_trips(&grid);
+        // A classic level is saved as the oldest version that can hold it
+        assert_eq!(
+            Grid::stored_version(&grid.to_ron(), LevelFormat::Ron).unwrap(),
+            CLASSIC_VERSION
+        );
+    }
+
+    #[test]
+    fn levels_using_newer_features_save_the_current_version() {
+        let mut grid = Grid::from_text(SAMPLE).unwrap();
+        grid.rules.min_group = 3;
+        assert_eq!(
+            Grid::stored_version(&grid.to_ron(), LevelFormat::Ron).unwrap(),
+            FORMAT_VERSION
+        );
+    }
+
+    #[test]
+    fn migrate_brings_every_version_up_to_date() {
+        for version in 0..=FORMAT_VERSION {
+            assert_eq!(one_cell(version).migrate().unwrap().version, FORMAT_VERSION);
+        }
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let newer = FORMAT_VERSION + 1;
+        assert!(matches!(
+            one_cell(newer).migrate(),
+            Err(GridError::NewerVersion { version }) if version == newer
+        ));
+
+        let text = format!("(version: {newer}, steps: 3, height: 1, width: 1, data: [[Empty]])");
+        let Err(err) = Grid::from_text(&text) else {
+            panic!("version {newer} loaded");
+        };
+        assert!(err.to_string().contains(&format!("format version {newer}")), "{err}");
     }
 }
@@ -0,0 +1,109 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::MoveDir;
+
+// A wall on the edge between two cells. Walls only stop movement, groups still form across them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wall {
+    // The cell above or to the left of the edge
+    pub at: (usize, usize),
+    pub side: Side,
+    pub kind: WallKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    // Between `at` and the cell to its right
+    Right,
+    // Between `at` and the cell below it
+    Below,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WallKind {
+    // Nothing gets through
+    Solid,
+    // A gate tiles only cross moving this way
+    OneWay(MoveDir),
+}
+
+impl Wall {
+    // The wall on the edge a tile crosses leaving `at` in `direction`, None past the top or left of the board
+    pub fn on_edge((y, x): (usize, usize), direction: MoveDir, kind: WallKind) -> Option<Self> {
+        let (at, side) = match direction {
+            MoveDir::Right => ((y, x), Side::Right),
+            MoveDir::Down => ((y, x), Side::Below),
+            MoveDir::Left => ((y, x.checked_sub(1)?), Side::Right),
+            MoveDir::Up => ((y.checked_sub(1)?, x), Side::Below),
+        };
+        Some(Self { at, side, kind })
+    }
+
+    // The direction from `at` across the wall
+    pub fn direction(&self) -> MoveDir {
+        match self.side {
+            Side::Right => MoveDir::Right,
+            Side::Below => MoveDir::Down,
+        }
+    }
+
+    // The cell below or to the right of the edge
+    pub fn far_cell(&self) -> (usize, usize) {
+        let (y, x) = self.at;
+        match self.side {
+            Side::Right => (y, x + 1),
+            Side::Below => (y + 1, x),
+        }
+    }
+
+    // Whether a tile leaving `from` in `direction` crosses this wall's edge
+    pub fn is_crossed(&self, from: (usize, usize), direction: MoveDir) -> bool {
+        match (self.side, direction) {
+            (Side::Right, MoveDir::Right) | (Side::Below, MoveDir::Down) => self.at == from,
+            (Side::Right, MoveDir::Left) | (Side::Below, MoveDir::Up) => self.far_cell() == from,
+            _ => false,
+        }
+    }
+
+    // Whether this wall stops a tile leaving `from` in `direction`
+    pub fn blocks(&self, from: (usize, usize), direction: MoveDir) -> bool {
+        self.is_crossed(from, direction) && self.kind != WallKind::OneWay(direction)
+    }
+
+    // Whether a gate's direction actually crosses its edge
+    pub fn is_aligned(&self) -> bool {
+        match (self.kind, self.side) {
+            (WallKind::Solid, _) => true,
+            (WallKind::OneWay(direction), Side::Right) => matches!(direction, MoveDir::Left | MoveDir::Right),
+            (WallKind::OneWay(direction), Side::Below) => matches!(direction, MoveDir::Up | MoveDir::Down),
+        }
+    }
+}
+
+// Spelled "up", "down", "left" and "right" in the wall and gate lines of the compact format
+impl fmt::Display for MoveDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveDir::Up => write!(f, "up"),
+            MoveDir::Down => write!(f, "down"),
+            MoveDir::Left => write!(f, "left"),
+            MoveDir::Right => write!(f, "right"),
+        }
+    }
+}
+
+impl FromStr for MoveDir {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(MoveDir::Up),
+            "down" => Ok(MoveDir::Down),
+            "left" => Ok(MoveDir::Left),
+            "right" => Ok(MoveDir::Right),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+// Bounded undo/redo stacks of snapshots. The oldest undo entry is dropped once capacity is reached.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct History<T> {
+    undo_stack: VecDeque<T>,
+    redo_stack: Vec<T>,
+    capacity: usize,
+}
+
+impl<T> History<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::with_capacity(capacity),
+            redo_stack: Vec::new(),
+            capacity,
+        }
+    }
+
+    // Records the state before a change. Invalidates everything that could be redone.
+    pub fn record(&mut self, snapshot: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.undo_stack.len() == self.capacity {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(snapshot);
+        self.redo_stack.clear();
+    }
+
+    // Returns the previous state, keeping `current` so it can be redone
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo_stack.pop_back()?;
+        self.redo_stack.push(current);
+        Some(previous)
+    }
+
+    // Returns the next state, keeping `current` so it can be undone again
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push_back(current);
+        Some(next)
+    }
+
+    pub fn undo_count(&self) -> usize {
+        self.undo_stack.len()
+    }
+}
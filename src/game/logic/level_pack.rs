@@ -0,0 +1,50 @@
+use std::sync::OnceLock;
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use super::grid::Grid;
+
+const BUILTIN_PACK: &str = include_str!("../../../assets/levels.ron");
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LevelPack {
+    pub name: String,
+    pub levels: Vec<Level>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Level {
+    pub name: String,
+    pub grid: Grid,
+    // Fewest moves the level can be cleared in
+    #[serde(default)]
+    pub par: Option<usize>,
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+// Names a pack without holding a copy of it, so saved games find the pack as it is now
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackId {
+    Builtin,
+}
+
+impl PackId {
+    pub fn pack(self) -> &'static LevelPack {
+        match self {
+            PackId::Builtin => LevelPack::builtin(),
+        }
+    }
+}
+
+impl LevelPack {
+    pub fn builtin() -> &'static Self {
+        static BUILTIN: OnceLock<LevelPack> = OnceLock::new();
+        BUILTIN.get_or_init(|| Self::from_ron(BUILTIN_PACK).expect("built-in level pack is valid"))
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self> {
+        Ok(ron::de::from_str(ron)?)
+    }
+}
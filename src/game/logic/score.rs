@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::grid::{Outcome, event::GridEvent, rules::Rules};
+
+// Points for each cleared tile in a group of exactly the minimum size
+const TILE_POINTS: usize = 10;
+// Extra points per tile for each tile a group has past the minimum
+const SIZE_BONUS: usize = 5;
+// For every group after the first cleared at once, by the move or by the same cascade wave
+const GROUP_BONUS: usize = 50;
+// For every step left when the level is won
+const STEP_BONUS: usize = 20;
+
+// Points earned over a game, kept in parts so the result screen can break them down
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    // Cleared tiles, worth more per tile in bigger groups
+    pub tiles: usize,
+    // Clearing more than one group at once
+    pub groups: usize,
+    // Cascade waves multiply the tile points they clear, wave 2 doubling them and so on
+    pub cascades: usize,
+    // Steps left over at a win
+    pub steps: usize,
+}
+
+impl Score {
+    pub fn total(&self) -> usize {
+        self.tiles + self.groups + self.cascades + self.steps
+    }
+
+    // Adds the points for the clears in one move's events
+    pub fn add_move(&mut self, events: &[GridEvent], rules: &Rules) {
+        // (wave, group) -> tiles cleared or cracked
+        let mut groups: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        for key in events.iter().filter_map(GridEvent::cleared_in) {
+            *groups.entry(key).or_default() += 1;
+        }
+
+        for (&(wave, group), &size) in &groups {
+            let points = size * (TILE_POINTS + SIZE_BONUS * size.saturating_sub(rules.min_group));
+            self.tiles += points;
+            self.cascades += points * (wave - 1);
+            if group > 0 {
+                self.groups += GROUP_BONUS;
+            }
+        }
+    }
+
+    // Adds the bonus for unused steps once the game is over
+    pub fn finish(&mut self, outcome: Outcome, steps_left: usize) {
+        if outcome == Outcome::Won {
+            self.steps = STEP_BONUS * steps_left;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::grid::{Grid, MoveDir};
+
+    // The score for making `direction` on the level
+    fn score_move(text: &str, direction: MoveDir) -> Score {
+        let mut grid = Grid::from_text(text).unwrap();
+        let events = grid.apply_move(direction).unwrap();
+        let mut score = Score::default();
+        score.add_move(&events, &grid.rules);
+        score
+    }
+
+    #[test]
+    fn bigger_groups_are_worth_more_per_tile() {
+        assert_eq!(score_move("steps: 3\n\nRRR.R\n", MoveDir::Left).tiles, 4 * TILE_POINTS);
+        assert_eq!(
+            score_move("steps: 3\n\nRRRR.R\n", MoveDir::Left).tiles,
+            5 * (TILE_POINTS + SIZE_BONUS)
+        );
+        // The minimum comes from the level's rules
+        assert_eq!(
+            score_move("steps: 3\nmin_group: 3\n\nRRRR.R\n", MoveDir::Left).tiles,
+            5 * (TILE_POINTS + 2 * SIZE_BONUS)
+        );
+    }
+
+    #[test]
+    fn clearing_groups_at_once_earns_a_bonus() {
+        let score = score_move("steps: 3\n\nRRR.R\nGGG.G\nBBB.B\n", MoveDir::Left);
+        assert_eq!(score.tiles, 12 * TILE_POINTS);
+        assert_eq!(score.groups, 2 * GROUP_BONUS);
+        assert_eq!(score.total(), score.tiles + score.groups);
+    }
+
+    #[test]
+    fn cascade_waves_multiply_their_tile_points() {
+        let score = score_move("steps: 3\nmin_group: 3\ncascade: true\n\nRR.RGG.G\n", MoveDir::Left);
+        assert_eq!(score.tiles, 6 * TILE_POINTS);
+        // The second wave's three tiles count twice
+        assert_eq!(score.cascades, 3 * TILE_POINTS);
+        assert_eq!(score.groups, 0);
+    }
+
+    #[test]
+    fn unused_steps_only_count_at_a_win() {
+        let mut score = Score::default();
+        score.finish(Outcome::Lost, 3);
+        assert_eq!(score.steps, 0);
+        score.finish(Outcome::Won, 3);
+        assert_eq!(score.steps, 3 * STEP_BONUS);
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::game::logic::grid::{Grid, MoveDir, tile::TileKind};
+
+const DIRECTIONS: [MoveDir; 4] = [MoveDir::Up, MoveDir::Down, MoveDir::Left, MoveDir::Right];
+
+pub enum Solution {
+    // Shortest sequence of moves that clears every regular tile
+    Solved(Vec<MoveDir>),
+    // Every board reachable within the step budget was explored without clearing it
+    Unsolvable { explored: usize },
+    // The search hit the state limit before reaching a conclusion
+    GaveUp { explored: usize },
+}
+
+pub struct Solver {
+    state_limit: usize,
+    // Set from another thread to stop the search early, which then reports that it gave up
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+// A board as one byte per cell, each naming a kind of tile in `Kinds`. Shared between the visited set and
+// the queue.
+type Layout = Rc<[u8]>;
+
+// Numbers the kinds of tile the search comes across, so a board can be kept as a `Layout`
+#[derive(Default)]
+struct Kinds {
+    kinds: Vec<TileKind>,
+    numbers: HashMap<TileKind, u8>,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self {
+            state_limit: 1_000_000,
+            cancel: None,
+        }
+    }
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state_limit(mut self, state_limit: usize) -> Self {
+        self.state_limit = state_limit;
+        self
+    }
+
+    pub fn cancel_flag(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    // Breadth-first search over move sequences, so the first clear board found is a shortest solution.
+    // Boards are compared by layout only, ignoring tile ids and remaining steps: the first visit of a
+    // layout always has the most steps left, so later visits can never do better.
+    // Only layouts are kept, along with the board and move each one came from. A board is rebuilt from its
+    // layout when its turn comes, and the moves are traced back through the parents once it is clear.
+    pub fn solve(&self, grid: &Grid) -> Solution {
+        // A color that can never form a group big enough to clear rules out a win before any move
+        if !grid.short_colors().is_empty() {
+            return Solution::Unsolvable { explored: 1 };
+        }
+
+        let mut kinds = Kinds::default();
+        let Some(start) = kinds.layout(grid) else {
+            return Solution::GaveUp { explored: 1 };
+        };
+        let mut parents: Vec<Option<(usize, MoveDir)>> = vec![None];
+        let mut visited = HashSet::from([start.clone()]);
+        let mut queue = VecDeque::from([(0, grid.steps, start)]);
+        let mut board = grid.clone();
+
+        while let Some((index, steps, layout)) = queue.pop_front() {
+            if self.is_cancelled() {
+                return Solution::GaveUp {
+                    explored: parents.len(),
+                };
+            }
+            kinds.restore(&mut board, &layout);
+            board.steps = steps;
+            if board.is_cleared() {
+                return Solution::Solved(Self::backtrack(&parents, index));
+            }
+
+            for direction in DIRECTIONS {
+                let mut next = board.clone();
+                if next.apply_move(direction).is_none() {
+                    continue;
+                }
+                let Some(layout) = kinds.layout(&next) else {
+                    return Solution::GaveUp {
+                        explored: parents.len(),
+                    };
+                };
+                if visited.contains(&layout) {
+                    continue;
+                }
+                if parents.len() >= self.state_limit {
+                    return Solution::GaveUp {
+                        explored: parents.len(),
+                    };
+                }
+                visited.insert(layout.clone());
+                parents.push(Some((index, direction)));
+                queue.push_back((parents.len() - 1, next.steps, layout));
+            }
+        }
+
+        Solution::Unsolvable {
+            explored: parents.len(),
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    fn backtrack(parents: &[Option<(usize, MoveDir)>], mut index: usize) -> Vec<MoveDir> {
+        let mut moves = Vec::new();
+        while let Some((parent, direction)) = parents[index] {
+            moves.push(direction);
+            index = parent;
+        }
+        moves.reverse();
+        moves
+    }
+}
+
+impl Kinds {
+    // Every kind of tile is part of the layout, since blockers can clear and ice can crack. None if the
+    // search has met more kinds than a byte can number.
+    fn layout(&mut self, grid: &Grid) -> Option<Layout> {
+        grid.get_tiles_view()
+            .iter()
+            .map(|tile| self.number(tile.kind()))
+            .collect()
+    }
+
+    fn number(&mut self, kind: TileKind) -> Option<u8> {
+        if let Some(&number) = self.numbers.get(&kind) {
+            return Some(number);
+        }
+        let number = u8::try_from(self.kinds.len()).ok()?;
+        self.kinds.push(kind);
+        self.numbers.insert(kind, number);
+        Some(number)
+    }
+
+    fn restore(&self, grid: &mut Grid, layout: &[u8]) {
+        for (tile, &number) in grid.tiles.iter_mut().zip(layout) {
+            *tile = self.kinds[number as usize].to_tile();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::level_pack::LevelPack;
+
+    fn level(board: &str, steps: usize) -> Grid {
+        Grid::from_text(&format!("steps: {steps}\n\n{board}")).unwrap()
+    }
+
+    fn solve(grid: &Grid) -> Vec<MoveDir> {
+        match Solver::new().solve(grid) {
+            Solution::Solved(moves) => moves,
+            Solution::Unsolvable { explored } => panic!("no solution in {explored} boards"),
+            Solution::GaveUp { explored } => panic!("gave up after {explored} boards"),
+        }
+    }
+
+    #[test]
+    fn finds_the_shortest_solution() {
+        assert_eq!(solve(&level("RRR.R\n", 3)), [MoveDir::Left]);
+        assert_eq!(solve(&level("R...\n.RR.\n...R\n", 5)).len(), 2);
+        // A wildcard makes up the fourth tile
+        assert_eq!(solve(&level("RRR.*\n", 1)), [MoveDir::Left]);
+    }
+
+    #[test]
+    fn reports_boards_without_a_solution() {
+        // Clearable, but not in a single step
+        let grid = level("R...\n.R.R\nR...\n", 1);
+        assert!(matches!(Solver::new().solve(&grid), Solution::Unsolvable { explored } if explored > 1));
+        assert_eq!(solve(&level("R...\n.R.R\nR...\n", 5)).len(), 2);
+
+        // Three red tiles can never make a group of four, so there is nothing to search
+        let grid = level("RRR.\nGGGG\n", 5);
+        assert!(matches!(
+            Solver::new().solve(&grid),
+            Solution::Unsolvable { explored: 1 }
+        ));
+    }
+
+    #[test]
+    fn gives_up_at_the_state_limit() {
+        let grid = level("R.G.B.\n.B.R.G\nG.R.B.\n.G.B.R\n", 100);
+        assert!(matches!(
+            Solver::new().state_limit(10).solve(&grid),
+            Solution::GaveUp { explored: 10 }
+        ));
+    }
+
+    #[test]
+    fn stops_once_cancelled() {
+        let grid = level("R.G.B.\n.B.R.G\nG.R.B.\n.G.B.R\n", 100);
+        let cancel = Arc::new(AtomicBool::new(true));
+        assert!(matches!(
+            Solver::new().cancel_flag(cancel).solve(&grid),
+            Solution::GaveUp { explored: 1 }
+        ));
+    }
+
+    #[test]
+    fn builtin_pack_pars_are_shortest_solutions() {
+        for level in &LevelPack::builtin().levels {
+            assert_eq!(Some(solve(&level.grid).len()), level.par, "{}", level.name);
+        }
+    }
+}
@@ -1,4 +1,7 @@
+pub mod anim;
 pub mod anim_widgets;
+pub mod animator;
 pub mod grid_layout;
 pub mod grid_widget;
+pub mod level_preview;
 pub mod tile_widget;
@@ -3,14 +3,17 @@ use std::{
     time::{Duration, Instant},
 };
 
-use super::{MoveDir, tile::Tile};
+use crate::game::logic::grid::{MoveDir, event::GridEvent, tile::Tile};
 
 #[derive(Clone, Copy)]
 pub enum Animation {
     Moving {
         tile: Tile,
         from: (usize, usize),
+        to: (usize, usize),
         direction: MoveDir,
+        // Portal the tile comes out of, drawn as the start of the slide instead of `from`
+        through: Option<(usize, usize)>,
         start_time: Instant,
     },
     Clearing {
@@ -21,25 +24,47 @@ pub enum Animation {
 }
 
 impl Animation {
+    pub fn from_event(event: GridEvent, start_time: Instant) -> Self {
+        match event {
+            GridEvent::Slide {
+                tile,
+                from,
+                to,
+                direction,
+                through,
+            } => Animation::Moving {
+                tile,
+                from,
+                to,
+                direction,
+                through,
+                start_time,
+            },
+            // Cracking ice plays as its shell clearing, leaving the tile underneath
+            GridEvent::Clear { tile, at, .. } | GridEvent::Crack { tile, at, .. } => {
+                Animation::Clearing { tile, at, start_time }
+            }
+        }
+    }
+
     pub fn duration(&self) -> Duration {
         match self {
-            Animation::Moving { .. } => Duration::from_millis(300),
+            // Longer slides take a little longer, so they don't look like jumps
+            Animation::Moving { from, to, through, .. } => {
+                let from = through.unwrap_or(*from);
+                let distance = from.0.abs_diff(to.0) + from.1.abs_diff(to.1);
+                Duration::from_millis(250 + 50 * distance as u64)
+            }
             Animation::Clearing { .. } => Duration::from_millis(150),
         }
     }
 
     // Returns Some containing target tile grid coordinates if self is Animation::Moving, returns None otherwise
     pub fn get_target(&self) -> Option<(usize, usize)> {
-        let Animation::Moving { from, direction, .. } = self else {
-            return None;
-        };
-        let (y, x) = *from;
-        Some(match direction {
-            MoveDir::Up => (y.saturating_sub(1), x),
-            MoveDir::Down => (y.saturating_add(1), x),
-            MoveDir::Left => (y, x.saturating_sub(1)),
-            MoveDir::Right => (y, x.saturating_add(1)),
-        })
+        match self {
+            Animation::Moving { to, .. } => Some(*to),
+            Animation::Clearing { .. } => None,
+        }
     }
 
     pub fn is_active(&self) -> bool {
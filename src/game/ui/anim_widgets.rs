@@ -4,8 +4,9 @@ mod moving;
 use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
 
 use crate::game::{
-    logic::grid::anim::Animation,
+    logic::grid::MoveDir,
     ui::{
+        anim::Animation,
         anim_widgets::{clearing::ClearingTile, moving::MovingTile},
         grid_layout::GridLayout,
     },
@@ -26,16 +27,43 @@ impl<'a> Widget for AnimationWidget<'a> {
     fn render(self, _rect: Rect, buf: &mut Buffer) {
         match self.anim {
             Animation::Moving {
-                tile, from, direction, ..
+                tile,
+                from,
+                to,
+                direction,
+                through,
+                ..
             } => {
-                let to = self.anim.get_target().unwrap();
-                let (from_rect, to_rect) = (
-                    self.grid_layout.get_rect_from_coords(*from),
-                    self.grid_layout.get_rect_from_coords(to),
+                let start = through.unwrap_or(*from);
+                let (mut from_rect, mut to_rect) = (
+                    self.grid_layout.get_rect_from_coords(start),
+                    self.grid_layout.get_rect_from_coords(*to),
                 );
 
+                // A tile that went off one edge and came back on the other ends up behind where it started.
+                // Unroll the move past the right or bottom edge, the tile wraps back when it is drawn.
+                let wraps = match direction {
+                    MoveDir::Right => to.1 < start.1,
+                    MoveDir::Left => to.1 > start.1,
+                    MoveDir::Down => to.0 < start.0,
+                    MoveDir::Up => to.0 > start.0,
+                };
+                if wraps {
+                    let (period_w, period_h) = self.grid_layout.period();
+                    match direction {
+                        MoveDir::Right => to_rect.x += period_w,
+                        MoveDir::Left => from_rect.x += period_w,
+                        MoveDir::Down => to_rect.y += period_h,
+                        MoveDir::Up => from_rect.y += period_h,
+                    }
+                }
+
                 let t = self.anim.get_quartic_out_progress();
-                MovingTile::new(tile, from_rect, to_rect, *direction, t).render(Default::default(), buf);
+                let mut moving = MovingTile::new(tile, from_rect, to_rect, *direction, t);
+                if wraps {
+                    moving = moving.wrap(self.grid_layout);
+                }
+                moving.render(Default::default(), buf);
             }
             Animation::Clearing { tile, at, .. } => {
                 let at_rect = self.grid_layout.get_rect_from_coords(*at);
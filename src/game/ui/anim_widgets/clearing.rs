@@ -4,7 +4,7 @@ use ratatui::{
     widgets::Widget,
 };
 
-use crate::game::logic::grid::tile::Tile;
+use crate::game::{logic::grid::tile::Tile, ui::tile_widget::tile_color};
 
 pub struct ClearingTile<'a> {
     tile: &'a Tile,
@@ -20,7 +20,7 @@ impl<'a> ClearingTile<'a> {
 
 impl<'a> Widget for ClearingTile<'a> {
     fn render(self, _rect: Rect, buf: &mut Buffer) {
-        let Tile::Regular { color, .. } = self.tile else {
+        let Some(color) = tile_color(self.tile) else {
             return;
         };
         let area = self.at;
@@ -71,7 +71,7 @@ impl<'a> Widget for ClearingTile<'a> {
                 };
 
                 if let Some(cell) = buf.cell_mut(Position::new(x, y)) {
-                    cell.set_symbol(symbol).set_fg(*color);
+                    cell.set_symbol(symbol).set_fg(color);
                 }
             }
         }
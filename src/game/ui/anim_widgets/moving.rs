@@ -1,11 +1,14 @@
 use ratatui::{
-    buffer::Buffer,
+    buffer::{Buffer, Cell},
     layout::{Position, Rect},
-    style::{Color, Style},
+    style::Color,
     widgets::Widget,
 };
 
-use crate::game::logic::grid::{MoveDir, tile::Tile};
+use crate::game::{
+    logic::grid::{MoveDir, tile::Tile},
+    ui::{grid_layout::GridLayout, tile_widget::tile_color},
+};
 
 pub struct MovingTile<'a> {
     tile: &'a Tile,
@@ -13,23 +16,43 @@ pub struct MovingTile<'a> {
     to: Rect,
     dir: MoveDir,
     t: f64,
+    // Set for moves across the edge of a wrap-around board
+    wrap: Option<&'a GridLayout>,
 }
 
 impl<'a> MovingTile<'a> {
     pub fn new(tile: &'a Tile, from: Rect, to: Rect, dir: MoveDir, t: f64) -> Self {
-        Self { tile, from, to, dir, t }
+        Self {
+            tile,
+            from,
+            to,
+            dir,
+            t,
+            wrap: None,
+        }
+    }
+
+    // Draws the parts of the tile that are past the board's edge on the other side
+    pub fn wrap(mut self, layout: &'a GridLayout) -> Self {
+        self.wrap = Some(layout);
+        self
+    }
+
+    fn cell_mut<'b>(&self, buf: &'b mut Buffer, x: u16, y: u16) -> Option<&'b mut Cell> {
+        let position = Position::new(x, y);
+        buf.cell_mut(self.wrap.map_or(position, |layout| layout.wrap_position(position)))
     }
 }
 
 impl<'a> Widget for MovingTile<'a> {
     fn render(self, _rect: Rect, buf: &mut Buffer) {
-        let Tile::Regular { color, .. } = self.tile else {
+        let Some(color) = tile_color(self.tile) else {
             return;
         };
 
         match self.dir {
-            MoveDir::Up | MoveDir::Down => self.render_vertical(*color, buf),
-            MoveDir::Left | MoveDir::Right => self.render_horizontal(*color, buf),
+            MoveDir::Up | MoveDir::Down => self.render_vertical(color, buf),
+            MoveDir::Left | MoveDir::Right => self.render_horizontal(color, buf),
         }
     }
 }
@@ -54,7 +77,7 @@ impl<'a> MovingTile<'a> {
 
         if !top_symbol.is_empty() {
             for dx in 0..rect.width {
-                if let Some(cell) = buf.cell_mut(Position::new(rect.x + dx, rect.y)) {
+                if let Some(cell) = self.cell_mut(buf, rect.x + dx, rect.y) {
                     cell.set_symbol(top_symbol).set_fg(color);
                 }
             }
@@ -62,13 +85,17 @@ impl<'a> MovingTile<'a> {
 
         if rect.height > 1 {
             let body_rect = Rect::new(rect.x, rect.y + 1, rect.width, rect.height - 1);
-            buf.set_style(body_rect, Style::default().bg(color));
+            for position in body_rect.positions() {
+                if let Some(cell) = self.cell_mut(buf, position.x, position.y) {
+                    cell.set_bg(color);
+                }
+            }
         }
 
         if !bottom_symbol.is_empty() {
             let overflow_y = rect.y + rect.height;
             for dx in 0..rect.width {
-                if let Some(cell) = buf.cell_mut(Position::new(rect.x + dx, overflow_y)) {
+                if let Some(cell) = self.cell_mut(buf, rect.x + dx, overflow_y) {
                     cell.set_symbol(bottom_symbol).set_fg(color);
                 }
             }
@@ -94,7 +121,7 @@ impl<'a> MovingTile<'a> {
 
         if !left_symbol.is_empty() {
             for dy in 0..rect.height {
-                if let Some(cell) = buf.cell_mut(Position::new(rect.x, rect.y + dy)) {
+                if let Some(cell) = self.cell_mut(buf, rect.x, rect.y + dy) {
                     cell.set_symbol(left_symbol).set_fg(color);
                 }
             }
@@ -102,13 +129,17 @@ impl<'a> MovingTile<'a> {
 
         if rect.width > 1 {
             let body_rect = Rect::new(rect.x + 1, rect.y, rect.width - 1, rect.height);
-            buf.set_style(body_rect, Style::default().bg(color));
+            for position in body_rect.positions() {
+                if let Some(cell) = self.cell_mut(buf, position.x, position.y) {
+                    cell.set_bg(color);
+                }
+            }
         }
 
         if !right_symbol.is_empty() {
             let overflow_x = rect.x + rect.width;
             for dy in 0..rect.height {
-                if let Some(cell) = buf.cell_mut(Position::new(overflow_x, rect.y + dy)) {
+                if let Some(cell) = self.cell_mut(buf, overflow_x, rect.y + dy) {
                     cell.set_symbol(right_symbol).set_fg(color);
                 }
             }
@@ -0,0 +1,106 @@
+use std::{collections::VecDeque, time::Instant};
+
+use ndarray::{Array2, ArrayView2};
+
+use crate::game::{
+    logic::grid::{
+        Grid,
+        event::GridEvent,
+        tile::{Moves, Tile},
+    },
+    ui::anim::Animation,
+};
+
+// Plays grid events back as animations, one phase at a time: all slides of a move, then all clears,
+// then the slides and clears of each cascade.
+#[derive(Default)]
+pub struct Animator {
+    active: Vec<Animation>,
+    // Events of the playing phase, applied to `board` once it finishes
+    active_events: Vec<GridEvent>,
+    pending: VecDeque<Vec<GridEvent>>,
+    // The board as it was when the playing phase started, None while idle
+    board: Option<Array2<Tile>>,
+}
+
+impl Animator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // `before` is the board the events start from
+    pub fn push_events(&mut self, before: &Grid, events: Vec<GridEvent>) {
+        if self.board.is_none() {
+            self.board = Some(before.tiles.clone());
+        }
+        // Consecutive events of the same kind play together
+        for event in events {
+            match self.pending.back_mut() {
+                Some(phase) if phase.last().is_some_and(|last| Self::same_phase(last, &event)) => phase.push(event),
+                _ => self.pending.push_back(vec![event]),
+            }
+        }
+        self.update();
+    }
+
+    pub fn update(&mut self) {
+        self.active.retain(|anim| anim.is_active());
+        if !self.active.is_empty() {
+            return;
+        }
+
+        if let Some(board) = &mut self.board {
+            for event in self.active_events.drain(..) {
+                match event {
+                    GridEvent::Slide { from, to, .. } => board.swap(from, to),
+                    GridEvent::Clear { at, .. } => board[at] = Tile::Empty,
+                    GridEvent::Crack { tile, at, .. } => {
+                        if let Tile::Ice { id, color } = tile {
+                            board[at] = Tile::Regular {
+                                id,
+                                color,
+                                moves: Moves::Any,
+                            };
+                        }
+                    }
+                }
+            }
+        }
+        match self.pending.pop_front() {
+            Some(phase) => {
+                let now = Instant::now();
+                self.active = phase.iter().map(|event| Animation::from_event(*event, now)).collect();
+                self.active_events = phase;
+            }
+            None => self.board = None,
+        }
+    }
+
+    pub fn skip(&mut self) {
+        self.active.clear();
+        self.active_events.clear();
+        self.pending.clear();
+        self.board = None;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.active.is_empty() && self.pending.is_empty()
+    }
+
+    pub fn get_active_slice(&self) -> &[Animation] {
+        &self.active
+    }
+
+    // What to draw under the active animations, None when the grid itself is up to date
+    pub fn get_board(&self) -> Option<ArrayView2<'_, Tile>> {
+        self.board.as_ref().map(|board| board.view())
+    }
+
+    fn same_phase(a: &GridEvent, b: &GridEvent) -> bool {
+        match (a, b) {
+            (GridEvent::Slide { .. }, GridEvent::Slide { .. }) => true,
+            // Each cascade wave clears in its own phase
+            _ => matches!((a.cleared_in(), b.cleared_in()), (Some((a, _)), Some((b, _))) if a == b),
+        }
+    }
+}
@@ -1,19 +1,36 @@
-use ratatui::layout::Rect;
+use ratatui::layout::{Position, Rect};
 
 pub struct GridLayout {
     rect_lookup: Vec<Rect>,
     grid_width: usize,
+    // The whole board, gaps after the last row and column included
+    bounds: Rect,
 }
 
 impl GridLayout {
-    pub fn new(rect_lookup: Vec<Rect>, grid_width: usize) -> Self {
+    pub fn new(rect_lookup: Vec<Rect>, grid_width: usize, bounds: Rect) -> Self {
         Self {
             rect_lookup,
             grid_width,
+            bounds,
         }
     }
 
     pub fn get_rect_from_coords(&self, (y, x): (usize, usize)) -> Rect {
         self.rect_lookup[y * self.grid_width + x]
     }
+
+    // The size of the board on screen, how far a tile travels to go once around a wrapping board
+    pub fn period(&self) -> (u16, u16) {
+        (self.bounds.width, self.bounds.height)
+    }
+
+    // Brings a position past the right or bottom of the board back in from the other side
+    pub fn wrap_position(&self, position: Position) -> Position {
+        let Rect { x, y, width, height } = self.bounds;
+        if position.x < x || position.y < y || width == 0 || height == 0 {
+            return position;
+        }
+        Position::new(x + (position.x - x) % width, y + (position.y - y) % height)
+    }
 }
@@ -3,32 +3,61 @@ use std::collections::HashSet;
 use ndarray::ArrayView2;
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    style::{Color, Modifier, Style},
     widgets::Widget,
 };
 
 use crate::game::{
-    logic::grid::{Grid, anim::Animation, tile::Tile},
-    ui::{anim_widgets::AnimationWidget, grid_layout::GridLayout},
+    logic::grid::{
+        Grid, MoveDir,
+        tile::Tile,
+        wall::{Side, Wall, WallKind},
+    },
+    ui::{anim::Animation, anim_widgets::AnimationWidget, animator::Animator, grid_layout::GridLayout},
 };
 
 pub struct GridWidget<'a> {
     tiles: ArrayView2<'a, Tile>,
+    walls: &'a [Wall],
     anim: &'a [Animation],
+    cursor: Option<(usize, usize)>,
 }
 
 impl<'a> GridWidget<'a> {
     pub fn new(grid: &'a Grid) -> Self {
         Self {
             tiles: grid.get_tiles_view(),
-            anim: grid.get_anims_slice(),
+            walls: &grid.walls,
+            anim: &[],
+            cursor: None,
         }
     }
+
+    pub fn animator(mut self, animator: &'a Animator) -> Self {
+        self.anim = animator.get_active_slice();
+        // Mid animation the grid is already ahead, so draw the board the animations start from
+        if let Some(board) = animator.get_board() {
+            self.tiles = board;
+        }
+        self
+    }
+
+    // Shades the tile at (y, x) so it stands out whatever its color
+    pub fn cursor(mut self, cursor: (usize, usize)) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
 }
 
 impl<'a> Widget for GridWidget<'a> {
     fn render(self, rect: Rect, buf: &mut Buffer) {
-        let rect = Rect::new(rect.x + 2, rect.y + 1, rect.width - 2, rect.height - 1);
+        let rect = Rect::new(
+            rect.x + 2,
+            rect.y + 1,
+            rect.width.saturating_sub(2),
+            rect.height.saturating_sub(1),
+        );
         let (height, width) = self.tiles.dim();
 
         let grid_rect = {
@@ -75,6 +104,9 @@ impl<'a> Widget for GridWidget<'a> {
                 );
 
                 rect_lookup.push(tile_rect);
+                if let Tile::Void = self.tiles[[y, x]] {
+                    continue;
+                }
                 if anim_mask.contains(&(y * width + x)) {
                     Tile::Empty.render(tile_rect, buf);
                 } else {
@@ -83,10 +115,64 @@ impl<'a> Widget for GridWidget<'a> {
             }
         }
 
-        let layout = &GridLayout::new(rect_lookup, width);
+        let layout = &GridLayout::new(rect_lookup, width, grid_rect);
+
+        if let Some(cursor) = self.cursor {
+            let cursor_rect = layout.get_rect_from_coords(cursor);
+            for position in cursor_rect.positions() {
+                buf[position].set_symbol("░").set_fg(Color::White);
+            }
+        }
 
         for animation in self.anim {
             AnimationWidget::new(animation, layout).render(Default::default(), buf);
         }
+
+        // On top of everything, so tiles sliding past never hide them
+        for wall in self.walls {
+            render_wall(wall, layout, buf);
+        }
+    }
+}
+
+// Walls sit in the gap after a tile, a solid line for walls and a dashed one with an arrow for gates
+fn render_wall(wall: &Wall, layout: &GridLayout, buf: &mut Buffer) {
+    let rect = layout.get_rect_from_coords(wall.at);
+    let (line, cells) = match wall.side {
+        Side::Right => (
+            ["┃", "┆"],
+            (rect.top()..=rect.bottom())
+                .map(|y| Position::new(rect.right(), y))
+                .collect::<Vec<_>>(),
+        ),
+        Side::Below => (
+            ["━", "┄"],
+            (rect.left()..rect.right() + 2)
+                .map(|x| Position::new(x, rect.bottom()))
+                .collect(),
+        ),
+    };
+    let (symbol, arrow, style) = match wall.kind {
+        WallKind::Solid => (line[0], None, Style::default().fg(Color::Gray)),
+        WallKind::OneWay(direction) => {
+            let arrow = match direction {
+                MoveDir::Up => "↑",
+                MoveDir::Down => "↓",
+                MoveDir::Left => "←",
+                MoveDir::Right => "→",
+            };
+            let style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+            (line[1], Some(arrow), style)
+        }
+    };
+    for &position in &cells {
+        if let Some(cell) = buf.cell_mut(position) {
+            cell.set_symbol(symbol).set_style(style);
+        }
+    }
+    if let Some(arrow) = arrow
+        && let Some(cell) = buf.cell_mut(cells[(cells.len() - 1) / 2])
+    {
+        cell.set_symbol(arrow).set_style(style);
     }
 }
@@ -0,0 +1,53 @@
+use color_eyre::eyre::Result;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, Padding, Paragraph, Widget, Wrap},
+};
+
+use crate::game::{logic::grid::Grid, ui::grid_widget::GridWidget};
+
+// A loaded level with any warnings underneath, or the reasons it failed to load
+pub struct LevelPreview<'a> {
+    level: &'a Result<Grid>,
+}
+
+impl<'a> LevelPreview<'a> {
+    pub fn new(level: &'a Result<Grid>) -> Self {
+        Self { level }
+    }
+}
+
+impl Widget for LevelPreview<'_> {
+    fn render(self, rect: Rect, buf: &mut Buffer) {
+        match self.level {
+            Ok(grid) => {
+                let warnings: Vec<Line> = grid
+                    .warnings()
+                    .iter()
+                    .map(|warning| Line::from(format!("⚠ {warning}")).fg(Color::Yellow))
+                    .collect();
+                let [grid_rect, warnings_rect] =
+                    Layout::vertical([Constraint::Fill(1), Constraint::Length(warnings.len() as u16 * 2)]).areas(rect);
+
+                GridWidget::new(grid).render(grid_rect, buf);
+                Paragraph::new(warnings)
+                    .wrap(Wrap { trim: true })
+                    .block(Block::new().padding(Padding::horizontal(2)))
+                    .render(warnings_rect, buf);
+            }
+            Err(err) => {
+                let block = Block::bordered()
+                    .title(" Invalid level ".bold())
+                    .border_style(Style::default().fg(Color::Red));
+                let causes: Vec<Line> = err.chain().map(|cause| Line::from(cause.to_string())).collect();
+                Paragraph::new(causes)
+                    .wrap(Wrap { trim: true })
+                    .block(block)
+                    .render(rect, buf);
+            }
+        }
+    }
+}
@@ -1,11 +1,20 @@
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::{Color, Style},
+    layout::{Position, Rect},
+    style::{Color, Modifier, Style},
     widgets::Widget,
 };
 
-use crate::game::logic::grid::tile::Tile;
+use crate::game::logic::grid::tile::{Moves, PALETTE, Tile};
+
+// Color a tile is drawn in while it moves or clears, None for tiles that are never animated
+pub fn tile_color(tile: &Tile) -> Option<Color> {
+    match tile {
+        Tile::Wildcard => Some(Color::White),
+        Tile::Crate => Some(Color::Gray),
+        _ => tile.color(),
+    }
+}
 
 impl Widget for &Tile {
     fn render(self, rect: Rect, buf: &mut Buffer) {
@@ -13,19 +22,95 @@ impl Widget for &Tile {
             Tile::Empty => {
                 buf.set_style(rect, Style::default().bg(Color::DarkGray));
             }
+            // Hatched, so blockers can't be mistaken for void cells, which draw nothing
             Tile::Blocker => {
-                /*
+                for position in rect.positions() {
+                    buf[position].set_symbol("╱").set_fg(Color::Gray);
+                }
+            }
+            Tile::Regular { color, moves, .. } => {
+                buf.set_style(rect, Style::default().bg(*color));
+                render_moves(*moves, rect, buf);
+            }
+            // Stripes of every palette color
+            Tile::Wildcard => {
                 for x in rect.left()..rect.right() {
-                    for y in rect.top()..rect.bottom() {
-                        let symbol = if (x + y) % 2 == 0 { "░" } else { "▒" };
-                        buf[(x, y)].set_symbol(symbol).set_fg(Color::Gray);
-                    }
+                    let color = PALETTE[(x - rect.x) as usize / 2 % PALETTE.len()];
+                    buf.set_style(Rect::new(x, rect.y, 1, rect.height), Style::default().bg(color));
                 }
-                */
             }
-            Tile::Regular { color, .. } => {
+            Tile::Bomb { color, .. } => {
+                buf.set_style(rect, Style::default().bg(*color));
+                set_center(rect, buf, "●", Style::default().fg(Color::Black));
+            }
+            // Frosted over, the color showing through
+            Tile::Ice { color, .. } => {
                 buf.set_style(rect, Style::default().bg(*color));
+                for position in rect.positions() {
+                    buf[position].set_symbol("░").set_fg(Color::White);
+                }
+            }
+            Tile::Portal { pair } => {
+                buf.set_style(rect, Style::default().bg(Color::DarkGray));
+                for position in rect.positions() {
+                    buf[position].set_symbol("░").set_fg(Color::Gray);
+                }
+                let label = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+                set_center(rect, buf, &pair.to_string(), label);
+            }
+            // Outside the board, so the background shows through
+            Tile::Void => {}
+            // Slats, so it reads as something that moves rather than a fixed blocker
+            Tile::Crate => {
+                buf.set_style(rect, Style::default().bg(Color::Gray));
+                for position in rect.positions() {
+                    buf[position].set_symbol("▒").set_fg(Color::DarkGray);
+                }
             }
         }
     }
 }
+
+// Pins in the corners of anchored tiles, a line along the only axis restricted tiles move on
+fn render_moves(moves: Moves, rect: Rect, buf: &mut Buffer) {
+    if rect.is_empty() {
+        return;
+    }
+    let style = Style::default().fg(Color::Black);
+    let (center_x, center_y) = (rect.x + (rect.width - 1) / 2, rect.y + (rect.height - 1) / 2);
+    let (symbol, positions): (&str, Vec<Position>) = match moves {
+        Moves::Any => return,
+        Moves::Anchored => (
+            "▪",
+            vec![
+                Position::new(rect.left(), rect.top()),
+                Position::new(rect.right() - 1, rect.top()),
+                Position::new(rect.left(), rect.bottom() - 1),
+                Position::new(rect.right() - 1, rect.bottom() - 1),
+            ],
+        ),
+        Moves::Horizontal => (
+            "─",
+            (rect.left()..rect.right())
+                .map(|x| Position::new(x, center_y))
+                .collect(),
+        ),
+        Moves::Vertical => (
+            "│",
+            (rect.top()..rect.bottom())
+                .map(|y| Position::new(center_x, y))
+                .collect(),
+        ),
+    };
+    for position in positions {
+        buf[position].set_symbol(symbol).set_style(style);
+    }
+}
+
+fn set_center(rect: Rect, buf: &mut Buffer, symbol: &str, style: Style) {
+    if rect.is_empty() {
+        return;
+    }
+    let center = Position::new(rect.x + (rect.width - 1) / 2, rect.y + (rect.height - 1) / 2);
+    buf[center].set_symbol(symbol).set_style(style);
+}
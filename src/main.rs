@@ -1,21 +1,30 @@
+mod cli;
 mod game;
 mod screens;
+mod storage;
 mod timer;
+mod worker;
 
 use std::time::Duration;
 
 use crate::{
-    screens::{Screen, ScreenAction, menu::MenuScreen},
+    cli::Cli,
+    screens::{Screen, ScreenAction},
     timer::Timer,
 };
+use clap::Parser;
 use color_eyre::eyre::Result;
-use ratatui::crossterm::event::{self, KeyCode, KeyModifiers};
+use ratatui::crossterm::event::{self, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{DefaultTerminal, crossterm::event::Event};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
-    ratatui::run(|terminal| -> Result<()> { App::new().run(terminal) })?;
+    // Errors from loading levels given on the command line surface here, before the terminal is taken over
+    let Some(screen_stack) = Cli::parse().run()? else {
+        return Ok(());
+    };
+    ratatui::run(|terminal| -> Result<()> { App::new(screen_stack).run(terminal) })?;
 
     Ok(())
 }
@@ -26,9 +35,9 @@ struct App {
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(screen_stack: Vec<Box<dyn Screen>>) -> Self {
         Self {
-            screen_stack: vec![Box::new(MenuScreen::main_menu())],
+            screen_stack,
             tick_timer: Timer::new(Duration::from_secs_f64(1.0 / 120.0)), // 120 TPS/FPS
         }
     }
@@ -36,45 +45,68 @@ impl App {
     fn run(mut self, terminal: &mut DefaultTerminal) -> Result<()> {
         terminal.clear()?;
 
-        loop {
-            if let Some(screen) = self.screen_stack.last() {
-                terminal.draw(|frame| screen.render_screen(frame))?;
-            } else {
-                break;
-            }
+        while let Some(screen) = self.screen_stack.last() {
+            terminal.draw(|frame| screen.render_screen(frame))?;
 
-            let mut input = None;
+            // Every key is delivered in order so pasted text and fast typing aren't dropped
+            let mut inputs = Vec::new();
 
             if event::poll(self.tick_timer.time_until_ready())? {
                 while event::poll(Duration::ZERO)? {
                     if let Event::Key(key) = event::read()? {
-                        match (key.modifiers, key.code) {
-                            (KeyModifiers::CONTROL, KeyCode::Char('c')) => self.screen_stack.clear(),
-                            (_, KeyCode::Esc) => {
-                                self.screen_stack.pop();
-                            }
-                            _ => {}
-                        }
-                        input = Some(key);
+                        inputs.push(key);
                     };
                 }
             }
 
-            if let Some(screen) = self.screen_stack.last_mut() {
-                let action = screen.update(input)?;
-                self.handle_action(action);
+            // Without input screens only need to advance once per tick, anything else would spin the CPU
+            if inputs.is_empty() && self.tick_timer.ready() {
+                self.dispatch(None)?;
+            }
+            for key in inputs {
+                match (key.modifiers, key.code) {
+                    (KeyModifiers::CONTROL, KeyCode::Char('c')) => self.truncate_stack(0),
+                    (_, KeyCode::Esc) => self.truncate_stack(self.screen_stack.len().saturating_sub(1)),
+                    _ => {}
+                }
+                self.dispatch(Some(key))?;
             }
         }
         Ok(())
     }
 
+    fn dispatch(&mut self, input: Option<KeyEvent>) -> Result<()> {
+        if let Some(screen) = self.screen_stack.last_mut() {
+            let action = screen.update(input)?;
+            self.handle_action(action);
+        }
+        Ok(())
+    }
+
     fn handle_action(&mut self, action: ScreenAction) {
         match action {
             ScreenAction::PushScreen(screen) => self.screen_stack.push(screen),
-            ScreenAction::PopScreen => {
-                self.screen_stack.pop();
+            ScreenAction::ReplaceScreen(screen) => {
+                if let Some(mut replaced) = self.screen_stack.pop() {
+                    replaced.on_exit();
+                }
+                self.screen_stack.push(screen);
+            }
+            ScreenAction::PopScreen => self.truncate_stack(self.screen_stack.len().saturating_sub(1)),
+            ScreenAction::PopToRoot => self.truncate_stack(1),
+            ScreenAction::Nothing => {}
+        }
+    }
+
+    // Pops screens until `len` remain, then lets the newly uncovered screen refresh itself
+    fn truncate_stack(&mut self, len: usize) {
+        while self.screen_stack.len() > len {
+            if let Some(mut screen) = self.screen_stack.pop() {
+                screen.on_exit();
             }
-            _ => {}
+        }
+        if let Some(screen) = self.screen_stack.last_mut() {
+            screen.on_resume();
         }
     }
 }
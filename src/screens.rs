@@ -1,6 +1,12 @@
+pub mod code_input;
+pub mod editor;
 pub mod file_picker;
 pub mod game;
+pub mod generating;
+pub mod level_select;
 pub mod menu;
+pub mod recent;
+pub mod result;
 
 use color_eyre::eyre::Result;
 use ratatui::crossterm::event::KeyEvent;
@@ -11,7 +17,11 @@ pub enum ScreenAction {
     #[default]
     Nothing,
     PushScreen(Box<dyn Screen>),
+    // Pops the current screen and pushes another in its place
+    ReplaceScreen(Box<dyn Screen>),
     PopScreen,
+    // Pops every screen except the main menu
+    PopToRoot,
 }
 
 impl<T: Screen + 'static> From<T> for ScreenAction {
@@ -23,4 +33,8 @@ impl<T: Screen + 'static> From<T> for ScreenAction {
 pub trait Screen {
     fn update(&mut self, key: Option<KeyEvent>) -> Result<ScreenAction>;
     fn render_screen(&self, frame: &mut Frame);
+    // Called when the screen above this one is popped
+    fn on_resume(&mut self) {}
+    // Called when this screen is popped or replaced, including when the app quits
+    fn on_exit(&mut self) {}
 }
@@ -0,0 +1,92 @@
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Constraint, Margin, Rect},
+    style::{Color, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    game::logic::grid::Grid,
+    screens::{Screen, ScreenAction, game::GameScreen},
+};
+
+// Prompt for a share code, typed or pasted
+pub struct CodeInputScreen {
+    code: String,
+    error: Option<String>,
+}
+
+impl CodeInputScreen {
+    pub fn new() -> Self {
+        Self {
+            code: String::new(),
+            error: None,
+        }
+    }
+
+    fn submit(&mut self) -> ScreenAction {
+        match Grid::from_share_code(&self.code) {
+            Ok(grid) => ScreenAction::ReplaceScreen(Box::new(GameScreen::from_grid(grid))),
+            Err(err) => {
+                self.error = Some(err.to_string());
+                ScreenAction::Nothing
+            }
+        }
+    }
+}
+
+impl Screen for CodeInputScreen {
+    fn update(&mut self, event: Option<KeyEvent>) -> Result<ScreenAction> {
+        if let Some(key) = event {
+            match key.code {
+                KeyCode::Enter => return Ok(self.submit()),
+                KeyCode::Backspace => {
+                    self.code.pop();
+                    self.error = None;
+                }
+                // Pasted codes arrive one key at a time, so whitespace around them is dropped here
+                KeyCode::Char(c) if !c.is_whitespace() => {
+                    self.code.push(c);
+                    self.error = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(ScreenAction::Nothing)
+    }
+
+    fn render_screen(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+}
+
+impl Widget for &CodeInputScreen {
+    fn render(self, rect: Rect, buf: &mut Buffer) {
+        let mut lines = vec![
+            Line::from(vec![Span::raw("> "), Span::raw(&self.code).bold(), Span::raw("_")]),
+            Line::raw(""),
+        ];
+        match &self.error {
+            Some(error) => lines.push(Line::from(error.as_str()).fg(Color::Red)),
+            None => lines.push(Line::from("enter play · esc back").fg(Color::DarkGray)),
+        }
+
+        let width = (self.code.len() as u16 + 12).clamp(60, rect.width);
+        let popup_rect = rect.centered(Constraint::Length(width), Constraint::Length(7));
+        let block = Block::bordered()
+            .border_set(border::THICK)
+            .title(Line::from(" LEVEL CODE ".bold()).centered());
+        let inner_rect = block.inner(popup_rect);
+
+        Clear.render(popup_rect, buf);
+        block.render(popup_rect, buf);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .render(inner_rect.inner(Margin::new(2, 1)), buf);
+    }
+}
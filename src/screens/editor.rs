@@ -0,0 +1,311 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::Rect,
+    style::Stylize,
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Widget},
+};
+use uid::Id;
+
+use crate::{
+    game::{
+        logic::grid::{
+            Grid, LevelFormat, MoveDir,
+            tile::{Moves, PALETTE, Tile, TileKind},
+            wall::{Wall, WallKind},
+        },
+        ui::grid_widget::GridWidget,
+    },
+    screens::{Screen, ScreenAction, game::GameScreen},
+};
+
+const MAX_SIZE: usize = 16;
+const MAX_PORTAL_PAIRS: u8 = 9;
+
+pub struct EditorScreen {
+    grid: Grid,
+    // None until a new level is saved for the first time
+    path: Option<PathBuf>,
+    cursor: (usize, usize),
+    modified: bool,
+    status: Option<String>,
+}
+
+impl EditorScreen {
+    pub fn new() -> Self {
+        Self::from_grid(Grid::new(6, 6, 10), None)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Ok(Self::from_grid(Grid::from_file(path)?, Some(path.to_path_buf())))
+    }
+
+    fn from_grid(grid: Grid, path: Option<PathBuf>) -> Self {
+        Self {
+            grid,
+            path,
+            cursor: (0, 0),
+            modified: false,
+            status: None,
+        }
+    }
+
+    fn move_cursor(&mut self, dy: isize, dx: isize) {
+        let (y, x) = self.cursor;
+        self.cursor = (
+            y.saturating_add_signed(dy).min(self.grid.get_height() - 1),
+            x.saturating_add_signed(dx).min(self.grid.get_width() - 1),
+        );
+    }
+
+    // Steps through Empty, Blocker and a regular tile of each palette color. A regular tile keeps the ways
+    // it can move.
+    fn cycle_tile(&mut self, offset: isize) {
+        let kinds = PALETTE.len() as isize + 2;
+        let tile = &mut self.grid.tiles[[self.cursor.0, self.cursor.1]];
+        let moves = match *tile {
+            Tile::Regular { moves, .. } => moves,
+            _ => Moves::Any,
+        };
+        let index = match tile.kind() {
+            TileKind::Empty => 0,
+            TileKind::Regular(color, _) | TileKind::Bomb(color) | TileKind::Ice(color) => {
+                PALETTE.iter().position(|c| *c == color).map_or(1, |i| i as isize + 2)
+            }
+            TileKind::Blocker | TileKind::Wildcard | TileKind::Portal(_) | TileKind::Crate | TileKind::Void => 1,
+        };
+        *tile = match (index + offset).rem_euclid(kinds) {
+            0 => Tile::Empty,
+            1 => Tile::Blocker,
+            i => Tile::Regular {
+                id: Id::new(),
+                color: PALETTE[i as usize - 2],
+                moves,
+            },
+        };
+        self.modified = true;
+    }
+
+    // Turns a colored tile into the next of regular, bomb and ice, keeping its color
+    fn cycle_special(&mut self) {
+        let tile = &mut self.grid.tiles[[self.cursor.0, self.cursor.1]];
+        let kind = match tile.kind() {
+            TileKind::Regular(color, _) => TileKind::Bomb(color),
+            TileKind::Bomb(color) => TileKind::Ice(color),
+            TileKind::Ice(color) => TileKind::Regular(color, Moves::Any),
+            _ => return,
+        };
+        *tile = kind.to_tile();
+        self.modified = true;
+    }
+
+    // Steps a regular tile through moving every way, anchored, horizontal only and vertical only
+    fn cycle_moves(&mut self) {
+        let Tile::Regular { moves, .. } = &mut self.grid.tiles[[self.cursor.0, self.cursor.1]] else {
+            return;
+        };
+        *moves = match moves {
+            Moves::Any => Moves::Anchored,
+            Moves::Anchored => Moves::Horizontal,
+            Moves::Horizontal => Moves::Vertical,
+            Moves::Vertical => Moves::Any,
+        };
+        self.modified = true;
+    }
+
+    // Places a portal, or moves an existing one on to the next pair number
+    fn cycle_portal(&mut self) {
+        let pair = match self.grid.tiles[[self.cursor.0, self.cursor.1]] {
+            Tile::Portal { pair } => pair % MAX_PORTAL_PAIRS + 1,
+            _ => 1,
+        };
+        self.set_tile(Tile::Portal { pair });
+    }
+
+    // Steps the edge on the cursor's right or bottom side through no wall, a wall, and a gate each way
+    fn cycle_wall(&mut self, direction: MoveDir) {
+        let Some(edge) = Wall::on_edge(self.cursor, direction, WallKind::Solid) else {
+            return;
+        };
+        let (y, x) = edge.far_cell();
+        if y >= self.grid.get_height() || x >= self.grid.get_width() {
+            return;
+        }
+        let back = match direction {
+            MoveDir::Right => MoveDir::Left,
+            _ => MoveDir::Up,
+        };
+        let existing = self
+            .grid
+            .walls
+            .iter()
+            .position(|wall| (wall.at, wall.side) == (edge.at, edge.side));
+        let kind = match existing.map(|index| self.grid.walls.remove(index).kind) {
+            None => Some(WallKind::Solid),
+            Some(WallKind::Solid) => Some(WallKind::OneWay(direction)),
+            Some(WallKind::OneWay(gate)) if gate == direction => Some(WallKind::OneWay(back)),
+            Some(WallKind::OneWay(_)) => None,
+        };
+        if let Some(kind) = kind {
+            self.grid.walls.push(Wall { kind, ..edge });
+        }
+        self.modified = true;
+    }
+
+    fn set_tile(&mut self, tile: Tile) {
+        self.grid.tiles[[self.cursor.0, self.cursor.1]] = tile;
+        self.modified = true;
+    }
+
+    fn resize(&mut self, dy: isize, dx: isize) {
+        let (old_height, old_width) = (self.grid.get_height(), self.grid.get_width());
+        let (height, width) = (Self::resized(old_height, dy), Self::resized(old_width, dx));
+        if (height, width) == (old_height, old_width) {
+            return;
+        }
+        self.grid.resize(height, width);
+        self.move_cursor(0, 0);
+        self.modified = true;
+    }
+
+    // Only growing is capped at MAX_SIZE, bigger boards opened from files keep their size until shrunk
+    fn resized(size: usize, offset: isize) -> usize {
+        let resized = size.saturating_add_signed(offset).max(1);
+        if resized > size {
+            resized.min(MAX_SIZE.max(size))
+        } else {
+            resized
+        }
+    }
+
+    fn change_steps(&mut self, offset: isize) {
+        self.grid.steps = self.grid.steps.saturating_add_signed(offset);
+        self.modified = true;
+    }
+
+    // New levels get the first free level-N.ron name in the working directory
+    fn save(&mut self) {
+        let path = self.path.clone().unwrap_or_else(|| {
+            let dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            (1..)
+                .map(|n| dir.join(format!("level-{n}.ron")))
+                .find(|path| !path.exists())
+                .unwrap_or_default()
+        });
+        let format = LevelFormat::from_path(&path);
+        // Unpaired portals can be placed while editing, but a level file with them would not load again
+        let text = match self
+            .grid
+            .to_text(format)
+            .and_then(|text| Grid::parse(&text, format).map(|_| text))
+        {
+            Ok(text) => text,
+            Err(err) => {
+                self.status = Some(format!("not saved: {err:#}"));
+                return;
+            }
+        };
+        self.status = Some(match fs::write(&path, text) {
+            Ok(()) => {
+                self.modified = false;
+                self.path = Some(path.clone());
+                format!("saved to {}", path.display())
+            }
+            Err(err) => format!("could not save: {err}"),
+        });
+    }
+
+    fn name(&self) -> String {
+        match &self.path {
+            Some(path) => path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            None => "new level".to_string(),
+        }
+    }
+}
+
+impl Screen for EditorScreen {
+    fn update(&mut self, event: Option<KeyEvent>) -> Result<ScreenAction> {
+        if let Some(key) = event {
+            self.status = None;
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1, 0),
+                KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1, 0),
+                KeyCode::Left | KeyCode::Char('h') => self.move_cursor(0, -1),
+                KeyCode::Right | KeyCode::Char('l') => self.move_cursor(0, 1),
+                KeyCode::Enter | KeyCode::Char('r') | KeyCode::Char(' ') => self.cycle_tile(1),
+                KeyCode::Char('R') => self.cycle_tile(-1),
+                KeyCode::Char('t') => self.cycle_special(),
+                KeyCode::Char('w') => self.set_tile(Tile::Wildcard),
+                KeyCode::Char('o') => self.cycle_portal(),
+                KeyCode::Char('c') => self.set_tile(Tile::Crate),
+                KeyCode::Char('v') => self.set_tile(Tile::Void),
+                KeyCode::Char('m') => self.cycle_moves(),
+                KeyCode::Char('|') => self.cycle_wall(MoveDir::Right),
+                KeyCode::Char('_') => self.cycle_wall(MoveDir::Down),
+                KeyCode::Char('x') | KeyCode::Delete | KeyCode::Backspace => self.set_tile(Tile::Empty),
+                KeyCode::Char('K') => self.resize(-1, 0),
+                KeyCode::Char('J') => self.resize(1, 0),
+                KeyCode::Char('H') => self.resize(0, -1),
+                KeyCode::Char('L') => self.resize(0, 1),
+                KeyCode::Char('[') => self.change_steps(-1),
+                KeyCode::Char(']') => self.change_steps(1),
+                KeyCode::Char('{') => self.change_steps(-10),
+                KeyCode::Char('}') => self.change_steps(10),
+                KeyCode::Char('s') => self.save(),
+                KeyCode::Char('p') => return Ok(GameScreen::from_grid(self.grid.clone()).practice().into()),
+                _ => {}
+            }
+        }
+        Ok(ScreenAction::Nothing)
+    }
+
+    fn render_screen(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+}
+
+impl Widget for &EditorScreen {
+    fn render(self, rect: Rect, buf: &mut Buffer) {
+        let header = Line::from(vec![
+            " TILES ".bold(),
+            Span::raw(format!(
+                "· editing {}{} ",
+                self.name(),
+                if self.modified { "*" } else { "" }
+            )),
+        ]);
+        let help = Line::from(
+            " hjkl move · r/R tile · t bomb/ice · m moves · w wild · o portal · c crate · v void · |_ walls · x clear · HJKL resize · [] {} steps · s save · p play ",
+        );
+
+        let mut footer = vec![
+            Span::raw(" "),
+            Span::raw(self.grid.steps.to_string()).bold(),
+            Span::raw(" steps | "),
+            Span::raw(format!("{}x{}", self.grid.get_height(), self.grid.get_width())).bold(),
+            Span::raw(" "),
+        ];
+        if let Some(status) = &self.status {
+            footer.push(Span::raw(format!("| {status} ")));
+        }
+
+        let block = Block::bordered()
+            .title(header)
+            .title(help.right_aligned())
+            .border_set(border::THICK)
+            .title_bottom(Line::from(footer).centered());
+        let inner_rect = block.inner(rect);
+        block.render(rect, buf);
+
+        GridWidget::new(&self.grid).cursor(self.cursor).render(inner_rect, buf);
+    }
+}
@@ -4,7 +4,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use color_eyre::eyre::{Context, Result};
+use color_eyre::eyre::Result;
 use ratatui::{
     Frame,
     buffer::Buffer,
@@ -18,14 +18,18 @@ use ratatui::{
 };
 
 use crate::{
-    game::{logic::grid::Grid, ui::grid_widget::GridWidget},
-    screens::{Screen, ScreenAction, game::GameScreen},
+    game::{logic::grid::Grid, ui::level_preview::LevelPreview},
+    screens::{Screen, ScreenAction, editor::EditorScreen, game::GameScreen, result::format_duration},
+    storage::progress::{LevelRecord, Progress},
 };
 
 pub struct FileItem {
     name: String,
     path: PathBuf,
     is_dir: bool,
+    // Only filled in for the current directory
+    record: Option<LevelRecord>,
+    invalid: bool,
 }
 
 pub struct FilePickerScreen {
@@ -57,8 +61,14 @@ impl FilePickerScreen {
                 let is_dir = path.is_dir();
                 let name = entry.file_name().to_string_lossy().to_string() + (if is_dir { "/" } else { "" });
 
-                if is_dir | name.ends_with(".ron") {
-                    items.push(FileItem { name, path, is_dir });
+                if is_dir || name.ends_with(".ron") || name.ends_with(".tiles") {
+                    items.push(FileItem {
+                        name,
+                        path,
+                        is_dir,
+                        record: None,
+                        invalid: false,
+                    });
                 }
             }
         }
@@ -71,7 +81,14 @@ impl FilePickerScreen {
     }
 
     fn refresh_items(&mut self) {
+        let progress = Progress::load();
         self.items = Self::get_items_in_dir(&self.current_dir);
+        for item in self.items.iter_mut().filter(|item| !item.is_dir) {
+            match Grid::from_file(&item.path) {
+                Ok(grid) => item.record = progress.get(&grid),
+                Err(_) => item.invalid = true,
+            }
+        }
         self.state.borrow_mut().select(Some(0));
     }
 
@@ -100,16 +117,13 @@ impl FilePickerScreen {
     }
 
     fn go_left(&mut self) {
-        match self.current_dir.parent() {
-            Some(parent) => {
-                let child_dir = self.current_dir.clone();
-                self.current_dir = parent.to_path_buf();
-                self.refresh_items();
-                self.state
-                    .borrow_mut()
-                    .select(self.items.iter().position(|i| i.path == child_dir));
-            }
-            None => {}
+        if let Some(parent) = self.current_dir.parent() {
+            let child_dir = self.current_dir.clone();
+            self.current_dir = parent.to_path_buf();
+            self.refresh_items();
+            self.state
+                .borrow_mut()
+                .select(self.items.iter().position(|i| i.path == child_dir));
         }
     }
 
@@ -118,24 +132,50 @@ impl FilePickerScreen {
             // Nothing selected
             return Ok(ScreenAction::Nothing);
         };
-        if let Some(FileItem { path, is_dir, .. }) = self.items.get(index) {
-            if *is_dir {
+        match self.items.get(index) {
+            Some(FileItem { path, is_dir: true, .. }) => {
                 self.current_dir = path.clone();
                 self.refresh_items();
-            } else {
-                return Self::get_action_from_grid(Self::load_grid_from_path(path));
             }
+            // The preview already explains why an invalid level can't be opened. A file that broke since the
+            // listing was read is ignored rather than ending the app.
+            Some(FileItem {
+                path, invalid: false, ..
+            }) => {
+                if let Ok(screen) = GameScreen::from_file(path) {
+                    return Ok(screen.into());
+                }
+            }
+            _ => {}
         }
         Ok(ScreenAction::Nothing)
     }
 
-    fn get_action_from_grid(grid: Result<Grid>) -> Result<ScreenAction> {
-        Ok(GameScreen::from_grid(grid?).into())
+    fn edit_current(&self) -> Result<ScreenAction> {
+        let index = self.state.borrow().selected();
+        match index.and_then(|index| self.items.get(index)) {
+            // Invalid and since-broken files are passed over the same way as when playing
+            Some(FileItem {
+                path,
+                is_dir: false,
+                invalid: false,
+                ..
+            }) => match EditorScreen::from_file(path) {
+                Ok(screen) => Ok(screen.into()),
+                Err(_) => Ok(ScreenAction::Nothing),
+            },
+            _ => Ok(ScreenAction::Nothing),
+        }
     }
 
-    fn load_grid_from_path(path: &Path) -> Result<Grid> {
-        let content = fs::read_to_string(&path).wrap_err("Could not read file")?;
-        Ok(Grid::from_ron(&content).wrap_err("Invalid RON format")?)
+    // Loads the first readable level file that comes after `path` in its directory listing
+    pub fn load_next_level(path: &Path) -> Option<(Grid, PathBuf)> {
+        let items = Self::get_items_in_dir(path.parent()?);
+        let index = items.iter().position(|item| item.path == path)?;
+        items[index + 1..]
+            .iter()
+            .filter(|item| !item.is_dir)
+            .find_map(|item| Some((Grid::from_file(&item.path).ok()?, item.path.clone())))
     }
 
     fn render_file_list(
@@ -162,7 +202,22 @@ impl FilePickerScreen {
                     style = style.underlined().italic();
                 }
 
-                ListItem::new(Line::from(Span::styled(&item.name, style)))
+                let mut spans = vec![Span::styled(&item.name, style)];
+                if item.invalid {
+                    spans.push(Span::styled(" ✗", Style::default().fg(Color::Red)));
+                }
+                if let Some(record) = item.record
+                    && record.completed
+                {
+                    spans.push(Span::styled(" ✓", Style::default().fg(Color::Green)));
+                    let mut best = format!(" {} moves · {}", record.best_moves, format_duration(record.best_time));
+                    if record.best_score > 0 {
+                        best += &format!(" · {} pts", record.best_score);
+                    }
+                    spans.push(Span::styled(best, Style::default().fg(Color::DarkGray)));
+                }
+
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
@@ -190,6 +245,7 @@ impl Screen for FilePickerScreen {
                 KeyCode::Up => self.go_up(),
                 KeyCode::Down => self.go_down(),
                 KeyCode::Right | KeyCode::Enter => return self.select_current(),
+                KeyCode::Char('e') => return self.edit_current(),
                 _ => {}
             }
         }
@@ -198,6 +254,12 @@ impl Screen for FilePickerScreen {
     fn render_screen(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.area());
     }
+    fn on_resume(&mut self) {
+        // A level may have been beaten since the list was loaded
+        let selected = self.state.borrow().selected();
+        self.refresh_items();
+        self.state.borrow_mut().select(selected);
+    }
 }
 
 impl Widget for &FilePickerScreen {
@@ -239,20 +301,15 @@ impl Widget for &FilePickerScreen {
             true,
         );
 
-        if let Some(idx) = self.state.borrow().selected() {
-            if let Some(item) = self.items.get(idx) {
-                match item.is_dir {
-                    true => {
-                        let child_items = FilePickerScreen::get_items_in_dir(&item.path);
-                        FilePickerScreen::render_file_list(&child_items, preview_rect, buf, None, false, false);
-                    }
-                    false => {
-                        match FilePickerScreen::load_grid_from_path(&item.path) {
-                            Ok(grid) => GridWidget::new(&grid).render(preview_rect, buf),
-                            Err(r) => {}
-                        };
-                    }
+        if let Some(idx) = self.state.borrow().selected()
+            && let Some(item) = self.items.get(idx)
+        {
+            match item.is_dir {
+                true => {
+                    let child_items = FilePickerScreen::get_items_in_dir(&item.path);
+                    FilePickerScreen::render_file_list(&child_items, preview_rect, buf, None, false, false);
                 }
+                false => LevelPreview::new(&Grid::from_file(&item.path)).render(preview_rect, buf),
             }
         }
     }
@@ -1,56 +1,350 @@
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
 use color_eyre::eyre::Result;
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::prelude::*;
 use ratatui::symbols::border;
 use ratatui::widgets::Block;
 
 use crate::{
     game::{
-        logic::grid::{Grid, MoveDir},
-        ui::grid_widget::GridWidget,
+        logic::{
+            generator::Generator,
+            grid::{Grid, MoveDir, Outcome, event::GridEvent},
+            history::History,
+            level_pack::PackId,
+            score::Score,
+            solver::{Solution, Solver},
+        },
+        ui::{animator::Animator, grid_widget::GridWidget},
+    },
+    screens::{
+        Screen, ScreenAction,
+        file_picker::FilePickerScreen,
+        result::{GameSummary, ResultScreen},
     },
-    screens::{Screen, ScreenAction},
+    storage::{daily::DailyRecord, progress::Progress, recent::RecentGames, session::Session},
+    worker::Worker,
 };
 
+// Where the level being played came from, used to find the next level
+#[derive(Clone, Serialize, Deserialize)]
+pub enum LevelSource {
+    File(PathBuf),
+    Pack { pack: PackId, index: usize },
+    Generated { generator: Generator, par: usize },
+    Daily { date: NaiveDate, par: usize },
+}
+
+impl LevelSource {
+    pub fn name(&self) -> String {
+        match self {
+            LevelSource::File(path) => path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            LevelSource::Pack { pack, index } => pack
+                .pack()
+                .levels
+                .get(*index)
+                .map_or_else(|| format!("Level {}", index + 1), |level| level.name.clone()),
+            LevelSource::Generated { generator, .. } => format!("Random {}", generator.describe()),
+            LevelSource::Daily { date, .. } => format!("Daily {date}"),
+        }
+    }
+
+    pub fn par(&self) -> Option<usize> {
+        match self {
+            LevelSource::File(_) => None,
+            LevelSource::Pack { pack, index } => pack.pack().levels.get(*index).and_then(|level| level.par),
+            LevelSource::Generated { par, .. } | LevelSource::Daily { par, .. } => Some(*par),
+        }
+    }
+
+    pub fn next_level(&self) -> Option<NextLevel> {
+        match self {
+            LevelSource::File(path) => FilePickerScreen::load_next_level(path)
+                .map(|(grid, path)| NextLevel::Ready(grid, LevelSource::File(path))),
+            LevelSource::Pack { pack, index } => pack.pack().levels.get(index + 1).map(|level| {
+                let source = LevelSource::Pack {
+                    pack: *pack,
+                    index: index + 1,
+                };
+                NextLevel::Ready(level.grid.clone(), source)
+            }),
+            LevelSource::Generated { generator, .. } => Some(NextLevel::Generate(generator.next_seed())),
+            // There is one daily puzzle per day
+            LevelSource::Daily { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum NextLevel {
+    Ready(Grid, LevelSource),
+    // Generating takes too long to do before the player even asks for the level
+    Generate(Generator),
+}
+
 pub struct GameScreen {
     grid: Grid,
+    level: Grid,
+    source: Option<LevelSource>,
+    started: Instant,
+    animator: Animator,
     input_queue: VecDeque<MoveDir>,
+    // Snapshots keep the score alongside the board so undo takes back the points too
+    history: History<(Grid, Score)>,
+    score: Score,
+    // Worked out after each move, undo and redo, checking for a legal move every frame is too slow
+    outcome: Option<Outcome>,
+    hint: Option<Solution>,
+    // The hint search runs in the background, the board can't be searched in one frame
+    hint_search: Option<Worker<Solution>>,
+    // Test plays from the editor leave saved games and records alone
+    practice: bool,
+    // Whether this game came from the saved session, only then does finishing it clear the save
+    resumed: bool,
+    show_code: bool,
+    // Clear waves of the last move and the most of any move, for levels with cascades
+    combo: usize,
+    best_combo: usize,
 }
 
+const UNDO_LIMIT: usize = 100;
+
 impl GameScreen {
     pub fn from_grid(grid: Grid) -> Self {
         GameScreen {
+            level: grid.clone(),
+            outcome: grid.outcome(),
             grid,
+            source: None,
+            started: Instant::now(),
+            animator: Animator::new(),
             input_queue: VecDeque::new(),
+            history: History::new(UNDO_LIMIT),
+            score: Score::default(),
+            hint: None,
+            hint_search: None,
+            practice: false,
+            resumed: false,
+            show_code: false,
+            combo: 0,
+            best_combo: 0,
         }
     }
+
+    // Loads a level file and adds it to the recent games list
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let grid = Grid::from_file(path)?;
+        let mut recent = RecentGames::load();
+        recent.record_opened(path);
+        let _ = recent.save();
+        Ok(GameScreen::from_grid(grid).with_source(LevelSource::File(path.to_path_buf())))
+    }
+
+    pub fn from_session(session: Session) -> Self {
+        GameScreen {
+            outcome: session.grid.outcome(),
+            grid: session.grid,
+            level: session.level,
+            source: session.source,
+            started: Instant::now().checked_sub(session.elapsed).unwrap_or_else(Instant::now),
+            animator: Animator::new(),
+            input_queue: VecDeque::new(),
+            history: session.history,
+            score: session.score,
+            hint: None,
+            hint_search: None,
+            practice: false,
+            resumed: true,
+            show_code: false,
+            combo: 0,
+            best_combo: 0,
+        }
+    }
+
+    fn to_session(&self) -> Session {
+        Session {
+            grid: self.grid.clone(),
+            level: self.level.clone(),
+            source: self.source.clone(),
+            history: self.history.clone(),
+            score: self.score,
+            elapsed: self.started.elapsed(),
+        }
+    }
+
+    pub fn with_source(mut self, source: LevelSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn practice(mut self) -> Self {
+        self.practice = true;
+        self
+    }
+
+    fn finish(&self, outcome: Outcome) -> ScreenAction {
+        let mut score = self.score;
+        score.finish(outcome, self.grid.steps);
+        let summary = GameSummary {
+            outcome,
+            moves_used: self.level.steps - self.grid.steps,
+            time_taken: self.started.elapsed(),
+            score,
+        };
+        if self.practice {
+            let screen = ResultScreen::new(summary, self.level.clone(), None).practice();
+            return ScreenAction::ReplaceScreen(Box::new(screen));
+        }
+        if outcome == Outcome::Won {
+            let mut progress = Progress::load();
+            progress.record_win(&self.level, summary.moves_used, summary.time_taken, score.total());
+            let _ = progress.save();
+        }
+        if let Some(LevelSource::File(path)) = &self.source {
+            let mut recent = RecentGames::load();
+            recent.record_result(path, outcome);
+            let _ = recent.save();
+        }
+        if outcome == Outcome::Won
+            && let Some(LevelSource::Daily { date, .. }) = &self.source
+        {
+            let mut daily = DailyRecord::load();
+            daily.record_win(*date, summary.moves_used, summary.time_taken);
+            let _ = daily.save();
+        }
+        let screen = ResultScreen::new(summary, self.level.clone(), self.source.clone());
+        ScreenAction::ReplaceScreen(Box::new(screen))
+    }
+
+    fn undo(&mut self) {
+        if let Some(previous) = self.history.undo((self.grid.clone(), self.score)) {
+            self.restore(previous);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(next) = self.history.redo((self.grid.clone(), self.score)) {
+            self.restore(next);
+        }
+    }
+
+    // Jumps straight to a snapshot, dropping queued moves and animations of the replaced board
+    fn restore(&mut self, (grid, score): (Grid, Score)) {
+        self.outcome = grid.outcome();
+        self.grid = grid;
+        self.score = score;
+        self.animator.skip();
+        self.input_queue.clear();
+        self.hint = None;
+        self.hint_search = None;
+        self.combo = 0;
+    }
+
+    fn waves(events: &[GridEvent]) -> usize {
+        events
+            .iter()
+            .filter_map(|event| event.cleared_in().map(|(wave, _)| wave))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn combo_spans(&self) -> Vec<Span<'_>> {
+        if !self.grid.rules.cascade {
+            return Vec::new();
+        }
+        vec![
+            Span::raw("| combo "),
+            Span::raw(format!("×{}", self.combo)).bold(),
+            Span::raw(format!(" (best ×{}) ", self.best_combo)),
+        ]
+    }
+
+    fn hint_spans(&self) -> Vec<Span<'_>> {
+        let hint = match &self.hint {
+            None if self.hint_search.is_some() => "thinking…".to_string(),
+            None => return Vec::new(),
+            Some(Solution::Solved(moves)) => match moves.first() {
+                Some(MoveDir::Up) => "↑".to_string(),
+                Some(MoveDir::Down) => "↓".to_string(),
+                Some(MoveDir::Left) => "←".to_string(),
+                Some(MoveDir::Right) => "→".to_string(),
+                None => "cleared".to_string(),
+            },
+            Some(Solution::Unsolvable { explored }) => format!("no solution in {explored} boards"),
+            Some(Solution::GaveUp { explored }) => format!("gave up after {explored} boards"),
+        };
+        vec![Span::raw("| hint: "), Span::raw(hint).bold(), Span::raw(" ")]
+    }
 }
 
 impl Screen for GameScreen {
     fn update(&mut self, event: Option<KeyEvent>) -> Result<ScreenAction> {
         if let Some(key) = event {
-            if self.input_queue.len() <= 2 {
-                let input = match key.code {
-                    KeyCode::Left => Some(MoveDir::Left),
-                    KeyCode::Right => Some(MoveDir::Right),
-                    KeyCode::Up => Some(MoveDir::Up),
-                    KeyCode::Down => Some(MoveDir::Down),
-                    _ => None,
-                };
-                self.input_queue.extend(input);
-            };
+            match (key.modifiers, key.code) {
+                (_, KeyCode::Char('h')) if self.hint.is_none() && self.hint_search.is_none() => {
+                    let grid = self.grid.clone();
+                    self.hint_search = Some(Worker::spawn(move |cancel| {
+                        Solver::new().state_limit(200_000).cancel_flag(cancel).solve(&grid)
+                    }));
+                }
+                (_, KeyCode::Char('u')) => self.undo(),
+                (_, KeyCode::Char('c')) => self.show_code = !self.show_code,
+                (KeyModifiers::CONTROL, KeyCode::Char('r')) => self.redo(),
+                (_, code) if self.input_queue.len() <= 2 => {
+                    let input = match code {
+                        KeyCode::Left => Some(MoveDir::Left),
+                        KeyCode::Right => Some(MoveDir::Right),
+                        KeyCode::Up => Some(MoveDir::Up),
+                        KeyCode::Down => Some(MoveDir::Down),
+                        _ => None,
+                    };
+                    self.input_queue.extend(input);
+                }
+                _ => {}
+            }
         }
 
-        self.grid.update_anim_state();
+        if let Some(search) = &mut self.hint_search
+            && let Some(result) = search.poll()
+        {
+            // A search that panicked just shows no hint, pressing h again retries it
+            self.hint = result.ok();
+            self.hint_search = None;
+        }
+
+        self.animator.update();
 
-        if self.grid.is_anim_completed()
+        if self.animator.is_idle()
             && let Some(input) = self.input_queue.pop_front()
         {
-            self.grid.move_grid(input);
+            let snapshot = self.grid.clone();
+            if let Some(events) = self.grid.apply_move(input) {
+                self.combo = Self::waves(&events);
+                self.best_combo = self.best_combo.max(self.combo);
+                let score = self.score;
+                self.score.add_move(&events, &self.grid.rules);
+                self.animator.push_events(&snapshot, events);
+                self.history.record((snapshot, score));
+                self.outcome = self.grid.outcome();
+                self.hint = None;
+                self.hint_search = None;
+            }
+        }
+
+        if self.animator.is_idle()
+            && self.input_queue.is_empty()
+            && let Some(outcome) = self.outcome
+        {
+            return Ok(self.finish(outcome));
         }
-        // TODO: Return ChangeState if all cleared. "Success!"
 
         Ok(ScreenAction::Nothing)
     }
@@ -58,31 +352,65 @@ impl Screen for GameScreen {
     fn render_screen(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.area());
     }
+
+    fn on_exit(&mut self) {
+        if self.practice {
+            return;
+        }
+        let _ = match self.outcome {
+            None => self.to_session().save(),
+            // Another unfinished game may be saved, finishing this one must not throw it away
+            Some(_) if self.resumed => Session::clear(),
+            Some(_) => Ok(()),
+        };
+    }
 }
 
 impl Widget for &GameScreen {
     fn render(self, rect: Rect, buf: &mut Buffer) {
-        let header = Line::from(" TILES ".bold());
+        let header = match &self.source {
+            Some(source) => Line::from(vec![" TILES ".bold(), Span::raw(format!("· {} ", source.name()))]),
+            None => Line::from(" TILES ".bold()),
+        };
 
         let footer = {
-            let spans = vec![
+            let mut spans = vec![
                 Span::raw(" "),
                 Span::raw(self.grid.steps.to_string()).bold(),
                 Span::raw(" moves remaining "),
+                Span::raw("| "),
+                Span::raw(self.history.undo_count().to_string()).bold(),
+                Span::raw(" undos "),
+                Span::raw("| score "),
+                Span::raw(self.score.total().to_string()).bold(),
+                Span::raw(" "),
+                Span::raw(format!("| {} ", self.grid.rules)),
             ];
+            spans.extend(self.combo_spans());
+            spans.extend(self.hint_spans());
 
             Line::from(spans)
         };
 
-        let block = Block::bordered()
+        let mut block = Block::bordered()
             .title(header)
             .border_set(border::THICK)
             .title_bottom(footer.centered());
+        if self.show_code {
+            // The code is for the level as it started, not the board in progress
+            let code = match self.level.to_share_code() {
+                Ok(code) => Span::raw(code).bold(),
+                Err(err) => Span::raw(err.to_string()),
+            };
+            block = block.title(Line::from(vec![Span::raw(" code: "), code, Span::raw(" ")]).right_aligned());
+        }
 
         let inner_rect = block.inner(rect);
 
         block.render(rect, buf);
 
-        GridWidget::new(&self.grid).render(inner_rect, buf);
+        GridWidget::new(&self.grid)
+            .animator(&self.animator)
+            .render(inner_rect, buf);
     }
 }
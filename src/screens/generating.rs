@@ -0,0 +1,125 @@
+use std::time::Instant;
+
+use chrono::{Datelike, NaiveDate};
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Constraint, Margin, Rect},
+    style::{Color, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    game::logic::generator::{GeneratedLevel, Generator},
+    screens::{
+        Screen, ScreenAction,
+        game::{GameScreen, LevelSource},
+    },
+    worker::Worker,
+};
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+// Generating can take seconds on larger boards, so it runs in the background behind this popup, which
+// replaces itself with the game once the level is ready
+pub struct GeneratingScreen {
+    name: String,
+    // Builds the game's level source from the par the generator found
+    source: Box<dyn Fn(usize) -> LevelSource>,
+    worker: Worker<Option<GeneratedLevel>>,
+    started: Instant,
+    failure: Option<String>,
+}
+
+impl GeneratingScreen {
+    fn new(name: String, generator: Generator, source: impl Fn(usize) -> LevelSource + 'static) -> Self {
+        let job = generator.clone();
+        Self {
+            name,
+            source: Box::new(source),
+            worker: Worker::spawn(move |cancel| job.generate_until(cancel)),
+            started: Instant::now(),
+            failure: None,
+        }
+    }
+
+    pub fn random(generator: Generator) -> Self {
+        let name = format!("Random {}", generator.describe());
+        Self::new(name, generator.clone(), move |par| LevelSource::Generated {
+            generator: generator.clone(),
+            par,
+        })
+    }
+
+    pub fn daily(date: NaiveDate) -> Self {
+        let generator = Generator::daily(date.num_days_from_ce() as u64);
+        Self::new(format!("Daily {date}"), generator, move |par| LevelSource::Daily {
+            date,
+            par,
+        })
+    }
+}
+
+impl Screen for GeneratingScreen {
+    fn update(&mut self, event: Option<KeyEvent>) -> Result<ScreenAction> {
+        if self.failure.is_some() {
+            return match event.map(|key| key.code) {
+                Some(KeyCode::Enter | KeyCode::Left) => Ok(ScreenAction::PopScreen),
+                _ => Ok(ScreenAction::Nothing),
+            };
+        }
+        match self.worker.poll() {
+            None => {}
+            Some(Ok(Some(level))) => {
+                let screen = GameScreen::from_grid(level.grid).with_source((self.source)(level.par));
+                return Ok(ScreenAction::ReplaceScreen(Box::new(screen)));
+            }
+            Some(Ok(None)) => {
+                self.failure = Some("No level found for these settings, try again for another board".to_string());
+            }
+            Some(Err(panic)) => self.failure = Some(format!("Generating the level failed: {panic}")),
+        }
+        Ok(ScreenAction::Nothing)
+    }
+
+    fn render_screen(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+}
+
+impl Widget for &GeneratingScreen {
+    fn render(self, rect: Rect, buf: &mut Buffer) {
+        let elapsed = self.started.elapsed();
+        let mut lines = vec![Line::from(self.name.as_str().bold()), Line::raw("")];
+        match &self.failure {
+            Some(failure) => {
+                lines.push(Line::from(failure.as_str()).fg(Color::Red));
+                lines.push(Line::from("enter back").fg(Color::DarkGray));
+            }
+            None => {
+                let frame = SPINNER[(elapsed.as_millis() / 80) as usize % SPINNER.len()];
+                lines.push(Line::from(vec![
+                    Span::raw(format!("{frame} looking for a solvable board ")),
+                    Span::raw(format!("{:.1}s", elapsed.as_secs_f64())).fg(Color::DarkGray),
+                ]));
+                lines.push(Line::from("esc cancel").fg(Color::DarkGray));
+            }
+        }
+
+        let popup_rect = rect.centered(Constraint::Length(60.min(rect.width)), Constraint::Length(8));
+        let block = Block::bordered()
+            .border_set(border::THICK)
+            .title(Line::from(" NEW LEVEL ".bold()).centered());
+        let inner_rect = block.inner(popup_rect);
+
+        Clear.render(popup_rect, buf);
+        block.render(popup_rect, buf);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .render(inner_rect.inner(Margin::new(2, 1)), buf);
+    }
+}
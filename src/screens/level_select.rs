@@ -0,0 +1,136 @@
+use std::cell::Cell;
+
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Widget},
+};
+
+use crate::{
+    game::{
+        logic::level_pack::{LevelPack, PackId},
+        ui::grid_widget::GridWidget,
+    },
+    screens::{
+        Screen, ScreenAction,
+        game::{GameScreen, LevelSource},
+    },
+};
+
+const THUMBNAIL_WIDTH: u16 = 28;
+const THUMBNAIL_HEIGHT: u16 = 12;
+
+pub struct LevelSelectScreen {
+    id: PackId,
+    pack: &'static LevelPack,
+    selected_index: usize,
+    // Thumbnails per row at the last render, used for up/down navigation
+    columns: Cell<usize>,
+}
+
+impl LevelSelectScreen {
+    pub fn new(id: PackId) -> Self {
+        Self {
+            id,
+            pack: id.pack(),
+            selected_index: 0,
+            columns: Cell::new(1),
+        }
+    }
+
+    fn select_current(&self) -> ScreenAction {
+        let Some(level) = self.pack.levels.get(self.selected_index) else {
+            return ScreenAction::Nothing;
+        };
+        let source = LevelSource::Pack {
+            pack: self.id,
+            index: self.selected_index,
+        };
+        GameScreen::from_grid(level.grid.clone()).with_source(source).into()
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        let target = self.selected_index as isize + offset;
+        if (0..self.pack.levels.len() as isize).contains(&target) {
+            self.selected_index = target as usize;
+        }
+    }
+}
+
+impl Screen for LevelSelectScreen {
+    fn update(&mut self, event: Option<KeyEvent>) -> Result<ScreenAction> {
+        if let Some(key) = event {
+            let columns = self.columns.get() as isize;
+            match key.code {
+                KeyCode::Left => self.move_selection(-1),
+                KeyCode::Right => self.move_selection(1),
+                KeyCode::Up => self.move_selection(-columns),
+                KeyCode::Down => self.move_selection(columns),
+                KeyCode::Enter => return Ok(self.select_current()),
+                _ => {}
+            }
+        }
+        Ok(ScreenAction::Nothing)
+    }
+
+    fn render_screen(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+}
+
+impl Widget for &LevelSelectScreen {
+    fn render(self, rect: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .border_set(border::THICK)
+            .title(Line::from(vec![
+                " TILES ".bold(),
+                Span::raw(format!("· {} ", self.pack.name)),
+            ]))
+            .title_bottom(Line::from(" ←↑↓→ select · enter play · esc back ").centered());
+        let inner_rect = block.inner(rect);
+        block.render(rect, buf);
+
+        let columns = (inner_rect.width / THUMBNAIL_WIDTH).max(1) as usize;
+        let visible_rows = (inner_rect.height / THUMBNAIL_HEIGHT).max(1) as usize;
+        self.columns.set(columns);
+
+        // Scroll just far enough to keep the selected row on screen
+        let first_row = (self.selected_index / columns).saturating_sub(visible_rows - 1);
+        let margin_x = (inner_rect.width - columns as u16 * THUMBNAIL_WIDTH.min(inner_rect.width)) / 2;
+
+        for (index, level) in self.pack.levels.iter().enumerate().skip(first_row * columns) {
+            let (row, column) = (index / columns - first_row, index % columns);
+            if row >= visible_rows {
+                break;
+            }
+
+            let thumbnail_rect = Rect::new(
+                inner_rect.x + margin_x + column as u16 * THUMBNAIL_WIDTH,
+                inner_rect.y + row as u16 * THUMBNAIL_HEIGHT,
+                THUMBNAIL_WIDTH,
+                THUMBNAIL_HEIGHT,
+            )
+            .intersection(inner_rect);
+
+            let is_selected = index == self.selected_index;
+            let title = format!(" {}. {} ", index + 1, level.name);
+            let mut thumbnail_block = Block::bordered()
+                .border_set(if is_selected { border::THICK } else { border::PLAIN })
+                .border_style(Style::default().fg(if is_selected { Color::Blue } else { Color::DarkGray }))
+                .title(Line::from(title).fg(if is_selected { Color::Blue } else { Color::White }));
+            if let Some(par) = level.par {
+                thumbnail_block = thumbnail_block.title_bottom(Line::from(format!(" par {par} ")).right_aligned());
+            }
+
+            let grid_rect = thumbnail_block.inner(thumbnail_rect);
+            thumbnail_block.render(thumbnail_rect, buf);
+            GridWidget::new(&level.grid).render(grid_rect, buf);
+        }
+    }
+}
@@ -1,5 +1,3 @@
-use std::fs;
-
 use color_eyre::eyre::Result;
 use ratatui::{
     Frame,
@@ -13,13 +11,51 @@ use ratatui::{
 };
 
 use crate::{
-    game::logic::grid::Grid,
-    screens::{Screen, ScreenAction, file_picker::FilePickerScreen, game::GameScreen},
+    game::logic::{
+        generator::{Difficulty, Generator},
+        level_pack::PackId,
+    },
+    screens::{
+        Screen, ScreenAction, code_input::CodeInputScreen, editor::EditorScreen, file_picker::FilePickerScreen,
+        game::GameScreen, generating::GeneratingScreen, level_select::LevelSelectScreen, recent::RecentScreen,
+    },
+    storage::{daily::DailyRecord, session::Session},
 };
 
 pub struct MenuOption<'a> {
     display: Line<'a>,
+    // Rebuilds `display` whenever the menu comes back into view
+    refresh_display: Option<Box<dyn Fn() -> Line<'a> + 'a>>,
     action: Box<dyn Fn() -> ScreenAction + 'a>,
+    is_enabled: Box<dyn Fn() -> bool + 'a>,
+    // Cached result of is_enabled, refreshed whenever the menu comes back into view
+    enabled: bool,
+}
+
+impl<'a> MenuOption<'a> {
+    pub fn new(display: Line<'a>, action: impl Fn() -> ScreenAction + 'a) -> Self {
+        Self {
+            display,
+            refresh_display: None,
+            action: Box::new(action),
+            is_enabled: Box::new(|| true),
+            enabled: true,
+        }
+    }
+
+    // For labels that show state which can change while the menu is covered
+    pub fn display_with(mut self, display: impl Fn() -> Line<'a> + 'a) -> Self {
+        self.display = display();
+        self.refresh_display = Some(Box::new(display));
+        self
+    }
+
+    // Greys the option out and ignores it while `is_enabled` returns false
+    pub fn enabled_if(mut self, is_enabled: impl Fn() -> bool + 'a) -> Self {
+        self.enabled = is_enabled();
+        self.is_enabled = Box::new(is_enabled);
+        self
+    }
 }
 
 pub struct MenuScreen<'a> {
@@ -37,49 +73,66 @@ impl<'a> MenuScreen<'a> {
 
     pub fn main_menu() -> Self {
         Self::new(vec![
-            MenuOption {
-                display: Line::raw("Begin Challenge"),
-                action: Box::new(|| {
-                    // TODO: open file picker and pick a .ron file
-                    ScreenAction::Nothing
-                }),
-            },
-            MenuOption {
-                display: Line::raw("Resume"),
-                action: Box::new(|| {
-                    // TODO: resume last played game
-                    let grid = Grid::from_ron(fs::read_to_string("grid.ron").unwrap().as_str()).unwrap();
-                    GameScreen::from_grid(grid).into()
-                }),
-            },
-            MenuOption {
-                display: Line::raw("Custom"),
-                action: Box::new(|| MenuScreen::custom_menu().into()),
-            },
-            MenuOption {
-                display: Line::raw("Quit"),
-                action: Box::new(|| ScreenAction::PopScreen),
-            },
+            MenuOption::new(Line::raw("Begin Challenge"), || {
+                LevelSelectScreen::new(PackId::Builtin).into()
+            }),
+            MenuOption::new(Line::raw("Daily"), || {
+                GeneratingScreen::daily(DailyRecord::today()).into()
+            })
+            .display_with(MenuScreen::daily_label),
+            MenuOption::new(Line::raw("Random puzzle"), || MenuScreen::random_menu().into()),
+            MenuOption::new(Line::raw("Resume"), || match Session::load() {
+                Some(session) => GameScreen::from_session(session).into(),
+                None => ScreenAction::Nothing,
+            })
+            .enabled_if(Session::exists),
+            MenuOption::new(Line::raw("Custom"), || MenuScreen::custom_menu().into()),
+            MenuOption::new(Line::raw("Quit"), || ScreenAction::PopScreen),
+        ])
+    }
+
+    fn daily_label() -> Line<'a> {
+        let record = DailyRecord::load();
+        let today = DailyRecord::today();
+        let mut spans = vec![Span::raw("Daily")];
+        if let Some(result) = record.get(today) {
+            spans.push(Span::raw(format!(" ✓ {} moves", result.moves)).fg(Color::Green));
+        }
+        match record.streak(today) {
+            0 => {}
+            streak => spans.push(Span::raw(format!(" · {streak} day streak")).fg(Color::DarkGray)),
+        }
+        Line::from(spans)
+    }
+
+    pub fn random_menu() -> Self {
+        let random = |generator: Generator| {
+            move || GeneratingScreen::random(generator.clone().seed(Generator::random_seed())).into()
+        };
+        Self::new(vec![
+            MenuOption::new(
+                Line::raw("Easy"),
+                random(Generator::new(5, 5).colors(2).difficulty(Difficulty::Easy)),
+            ),
+            MenuOption::new(
+                Line::raw("Normal"),
+                random(Generator::new(5, 5).colors(3).difficulty(Difficulty::Normal)),
+            ),
+            MenuOption::new(
+                Line::raw("Hard"),
+                random(Generator::new(6, 6).colors(3).difficulty(Difficulty::Hard)),
+            ),
+            MenuOption::new(Line::raw("Back"), || ScreenAction::PopScreen),
         ])
     }
 
     pub fn custom_menu() -> Self {
         Self::new(vec![
-            MenuOption {
-                display: Line::raw("Open local game"),
-                action: Box::new(|| FilePickerScreen::new().into()),
-            },
-            MenuOption {
-                display: Line::raw("Recent games"),
-                action: Box::new(|| {
-                    // give a list of recently opened games
-                    ScreenAction::Nothing
-                }),
-            },
-            MenuOption {
-                display: Line::raw("Back"),
-                action: Box::new(|| ScreenAction::PopScreen),
-            },
+            MenuOption::new(Line::raw("Open local game"), || FilePickerScreen::new().into()),
+            MenuOption::new(Line::raw("Recent games"), || RecentScreen::new().into()),
+            MenuOption::new(Line::raw("Enter level code"), || CodeInputScreen::new().into()),
+            MenuOption::new(Line::raw("Level editor"), || EditorScreen::new().into()),
+            MenuOption::new(Line::raw("Back"), || ScreenAction::PopScreen),
         ])
     }
 }
@@ -102,7 +155,12 @@ impl Screen for MenuScreen<'_> {
                         self.selected_index = 0;
                     }
                 }
-                KeyCode::Enter | KeyCode::Right => return Ok((self.options[self.selected_index].action)()),
+                KeyCode::Enter | KeyCode::Right => {
+                    let option = &self.options[self.selected_index];
+                    if option.enabled {
+                        return Ok((option.action)());
+                    }
+                }
                 KeyCode::Left => return Ok(ScreenAction::PopScreen),
                 _ => {}
             }
@@ -112,6 +170,14 @@ impl Screen for MenuScreen<'_> {
     fn render_screen(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.area());
     }
+    fn on_resume(&mut self) {
+        for option in &mut self.options {
+            option.enabled = (option.is_enabled)();
+            if let Some(display) = &option.refresh_display {
+                option.display = display();
+            }
+        }
+    }
 }
 
 impl Widget for &MenuScreen<'_> {
@@ -121,7 +187,7 @@ impl Widget for &MenuScreen<'_> {
         let gradient_area = Rect {
             x: rect.right().saturating_sub(width),
             y: rect.y,
-            width: width,
+            width,
             height: rect.height,
         };
 
@@ -166,7 +232,7 @@ impl Widget for &MenuScreen<'_> {
             }
         }
 
-        let title = vec![
+        let title = [
             "▀▀██▀▀ ▀▀██▀▀ ▀██▀   ▀██▀▀▀ ▄█▀▀█▄",
             "  ██     ██    ██     ██▄▄  ██▄▄  ",
             "  ██     ██    ██     ██▀▀    ▀▀██",
@@ -218,7 +284,13 @@ impl Widget for &MenuScreen<'_> {
                 let is_selected = i == self.selected_index;
                 let mut spans = Vec::new();
 
-                if is_selected {
+                if !option.enabled {
+                    // GREYED OUT
+                    spans.push(Span::raw(if is_selected { "> " } else { "  " }));
+                    for span in &option.display.spans {
+                        spans.push(span.clone().fg(Color::DarkGray));
+                    }
+                } else if is_selected {
                     // ARROW
                     spans.push(Span::styled("> ", Style::default()));
                     for span in &option.display.spans {
@@ -0,0 +1,200 @@
+use std::{
+    cell::RefCell,
+    time::{Duration, SystemTime},
+};
+
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget, Widget},
+};
+
+use crate::{
+    game::{
+        logic::grid::{Grid, Outcome},
+        ui::level_preview::LevelPreview,
+    },
+    screens::{Screen, ScreenAction, game::GameScreen},
+    storage::recent::RecentGames,
+};
+
+pub struct RecentScreen {
+    recent: RecentGames,
+    state: RefCell<ListState>,
+}
+
+impl RecentScreen {
+    pub fn new() -> Self {
+        let screen = Self {
+            recent: RecentGames::load(),
+            state: RefCell::new(ListState::default()),
+        };
+        if !screen.recent.entries().is_empty() {
+            screen.state.borrow_mut().select(Some(0));
+        }
+        screen
+    }
+
+    fn go_up(&mut self) {
+        let len = self.recent.entries().len();
+        if len == 0 {
+            return;
+        }
+        let i = self.state.borrow().selected().unwrap_or(0);
+        self.state
+            .borrow_mut()
+            .select(Some(if i > 0 { i - 1 } else { len - 1 }));
+    }
+
+    fn go_down(&mut self) {
+        let len = self.recent.entries().len();
+        if len == 0 {
+            return;
+        }
+        let i = self.state.borrow().selected().unwrap_or(0);
+        self.state
+            .borrow_mut()
+            .select(Some(if i < len - 1 { i + 1 } else { 0 }));
+    }
+
+    fn select_current(&mut self) -> Result<ScreenAction> {
+        let Some(entry) = self
+            .state
+            .borrow()
+            .selected()
+            .and_then(|i| self.recent.entries().get(i))
+        else {
+            return Ok(ScreenAction::Nothing);
+        };
+        // A file that changed since it was played stays listed, and its preview says why it won't open
+        match GameScreen::from_file(&entry.path) {
+            Ok(screen) => Ok(screen.into()),
+            Err(_) => Ok(ScreenAction::Nothing),
+        }
+    }
+
+    fn remove_current(&mut self) {
+        let Some(index) = self.state.borrow().selected() else {
+            return;
+        };
+        let Some(path) = self.recent.entries().get(index).map(|entry| entry.path.clone()) else {
+            return;
+        };
+        self.recent.remove(&path);
+        let _ = self.recent.save();
+
+        let len = self.recent.entries().len();
+        self.state
+            .borrow_mut()
+            .select(if len == 0 { None } else { Some(index.min(len - 1)) });
+    }
+
+    fn format_age(last_opened: SystemTime) -> String {
+        let age = SystemTime::now().duration_since(last_opened).unwrap_or(Duration::ZERO);
+        match age.as_secs() {
+            0..60 => "just now".to_string(),
+            secs @ 60..3600 => format!("{}m ago", secs / 60),
+            secs @ 3600..86400 => format!("{}h ago", secs / 3600),
+            secs => format!("{}d ago", secs / 86400),
+        }
+    }
+}
+
+impl Screen for RecentScreen {
+    fn update(&mut self, event: Option<KeyEvent>) -> Result<ScreenAction> {
+        if let Some(key) = event {
+            match key.code {
+                KeyCode::Up => self.go_up(),
+                KeyCode::Down => self.go_down(),
+                KeyCode::Char('d') | KeyCode::Delete => self.remove_current(),
+                KeyCode::Left => return Ok(ScreenAction::PopScreen),
+                KeyCode::Right | KeyCode::Enter => return self.select_current(),
+                _ => {}
+            }
+        }
+        Ok(ScreenAction::Nothing)
+    }
+    fn render_screen(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+    fn on_resume(&mut self) {
+        // Opening an entry moves it to the top and the game may have recorded a result
+        self.recent = RecentGames::load();
+        let len = self.recent.entries().len();
+        self.state.borrow_mut().select((len > 0).then_some(0));
+    }
+}
+
+impl Widget for &RecentScreen {
+    fn render(self, rect: Rect, buf: &mut Buffer) {
+        let big_block = Block::bordered()
+            .border_set(border::PLAIN)
+            .title(Line::from(vec![" TILES ".bold(), Span::raw("· Recent games ")]))
+            .title_bottom(Line::from(" ↑↓ select · enter play · d remove · esc back ").centered());
+
+        let inner_rect = big_block.inner(rect);
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(inner_rect);
+        let (list_rect, preview_rect) = (chunks[0], chunks[1]);
+
+        big_block.render(rect, buf);
+
+        if self.recent.entries().is_empty() {
+            Line::from(" No recent games ".fg(Color::DarkGray)).render(list_rect, buf);
+            return;
+        }
+
+        let list_items: Vec<ListItem> = self
+            .recent
+            .entries()
+            .iter()
+            .map(|entry| {
+                let result = match entry.last_result {
+                    Some(Outcome::Won) => Span::styled(" won ", Style::default().fg(Color::Green)),
+                    Some(Outcome::Lost) => Span::styled(" lost", Style::default().fg(Color::Red)),
+                    None => Span::raw("     "),
+                };
+                ListItem::new(Line::from(vec![
+                    result,
+                    Span::styled(
+                        format!(" {:>9} ", RecentScreen::format_age(entry.last_opened)),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(entry.path.display().to_string()),
+                ]))
+            })
+            .collect();
+
+        let block = Block::bordered()
+            .borders(Borders::RIGHT)
+            .border_style(Style::default().fg(Color::DarkGray))
+            .border_set(border::THICK);
+
+        StatefulWidget::render(
+            List::new(list_items)
+                .block(block)
+                .highlight_symbol("> ")
+                .highlight_style(Style::default().fg(Color::Blue)),
+            list_rect,
+            buf,
+            &mut self.state.borrow_mut(),
+        );
+
+        if let Some(entry) = self
+            .state
+            .borrow()
+            .selected()
+            .and_then(|i| self.recent.entries().get(i))
+        {
+            LevelPreview::new(&Grid::from_file(&entry.path)).render(preview_rect, buf);
+        }
+    }
+}
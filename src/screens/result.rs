@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Alignment, Constraint, Margin, Rect},
+    style::{Color, Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+
+use crate::{
+    game::logic::{
+        grid::{Grid, Outcome},
+        score::Score,
+    },
+    screens::{
+        Screen, ScreenAction,
+        game::{GameScreen, LevelSource, NextLevel},
+        generating::GeneratingScreen,
+    },
+};
+
+// Formats as minutes:seconds with tenths, e.g. 1:05.3
+pub fn format_duration(duration: Duration) -> String {
+    format!("{}:{:04.1}", duration.as_secs() / 60, duration.as_secs_f64() % 60.0)
+}
+
+pub struct GameSummary {
+    pub outcome: Outcome,
+    pub moves_used: usize,
+    pub time_taken: Duration,
+    pub score: Score,
+}
+
+#[derive(Clone, Copy)]
+enum ResultOption {
+    Retry,
+    NextLevel,
+    Menu,
+    Editor,
+}
+
+pub struct ResultScreen {
+    summary: GameSummary,
+    level: Grid,
+    source: Option<LevelSource>,
+    next_level: Option<NextLevel>,
+    options: Vec<ResultOption>,
+    selected_index: usize,
+    // Set for test plays, which return to the editor instead of the menu
+    practice: bool,
+}
+
+impl ResultScreen {
+    // `level` is the board as it was before the first move, used for retrying
+    pub fn new(summary: GameSummary, level: Grid, source: Option<LevelSource>) -> Self {
+        let next_level = source.as_ref().and_then(LevelSource::next_level);
+        let options = [
+            Some(ResultOption::Retry),
+            next_level.as_ref().map(|_| ResultOption::NextLevel),
+            Some(ResultOption::Menu),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        Self {
+            summary,
+            level,
+            source,
+            next_level,
+            options,
+            selected_index: 0,
+            practice: false,
+        }
+    }
+
+    pub fn practice(mut self) -> Self {
+        self.practice = true;
+        self.options = vec![ResultOption::Retry, ResultOption::Editor];
+        self
+    }
+
+    fn select_current(&mut self) -> ScreenAction {
+        match self.options[self.selected_index] {
+            ResultOption::Retry => {
+                let mut screen = GameScreen::from_grid(self.level.clone());
+                if self.practice {
+                    screen = screen.practice();
+                }
+                match &self.source {
+                    Some(source) => ScreenAction::ReplaceScreen(Box::new(screen.with_source(source.clone()))),
+                    None => ScreenAction::ReplaceScreen(Box::new(screen)),
+                }
+            }
+            ResultOption::NextLevel => match self.next_level.clone() {
+                Some(NextLevel::Ready(grid, source)) => {
+                    ScreenAction::ReplaceScreen(Box::new(GameScreen::from_grid(grid).with_source(source)))
+                }
+                Some(NextLevel::Generate(generator)) => {
+                    ScreenAction::ReplaceScreen(Box::new(GeneratingScreen::random(generator)))
+                }
+                None => ScreenAction::Nothing,
+            },
+            ResultOption::Menu => ScreenAction::PopToRoot,
+            ResultOption::Editor => ScreenAction::PopScreen,
+        }
+    }
+
+    fn option_label(option: ResultOption) -> &'static str {
+        match option {
+            ResultOption::Retry => "Retry",
+            ResultOption::NextLevel => "Next level",
+            ResultOption::Menu => "Main menu",
+            ResultOption::Editor => "Back to editor",
+        }
+    }
+}
+
+impl Screen for ResultScreen {
+    fn update(&mut self, event: Option<KeyEvent>) -> Result<ScreenAction> {
+        if let Some(key) = event {
+            match key.code {
+                KeyCode::Up => {
+                    if self.selected_index > 0 {
+                        self.selected_index -= 1;
+                    } else {
+                        self.selected_index = self.options.len() - 1;
+                    }
+                }
+                KeyCode::Down => {
+                    if self.selected_index < self.options.len() - 1 {
+                        self.selected_index += 1;
+                    } else {
+                        self.selected_index = 0;
+                    }
+                }
+                KeyCode::Enter | KeyCode::Right => return Ok(self.select_current()),
+                _ => {}
+            }
+        }
+        Ok(ScreenAction::Nothing)
+    }
+
+    fn render_screen(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+}
+
+impl Widget for &ResultScreen {
+    fn render(self, rect: Rect, buf: &mut Buffer) {
+        let (title, color) = match self.summary.outcome {
+            Outcome::Won => (" SUCCESS! ", Color::Green),
+            Outcome::Lost => (" GAME OVER ", Color::Red),
+        };
+
+        let time = self.summary.time_taken;
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw("Moves used: "),
+                Span::raw(self.summary.moves_used.to_string()).bold(),
+            ]),
+            Line::from(vec![Span::raw("Time taken: "), Span::raw(format_duration(time)).bold()]),
+        ];
+        if let Some(par) = self.source.as_ref().and_then(LevelSource::par) {
+            lines.push(Line::from(vec![
+                Span::raw("Par:        "),
+                Span::raw(par.to_string()).bold(),
+            ]));
+        }
+        lines.push(Line::raw(""));
+
+        let score = self.summary.score;
+        let parts = [
+            ("Tiles cleared", score.tiles),
+            ("Group bonus", score.groups),
+            ("Cascade bonus", score.cascades),
+            ("Steps left", score.steps),
+        ];
+        // Parts that earned nothing are left out to keep the breakdown short
+        lines.extend(
+            parts
+                .into_iter()
+                .filter(|&(_, points)| points > 0)
+                .map(|(label, points)| {
+                    Line::from(vec![
+                        Span::raw(format!("{label:<14}")),
+                        Span::raw(format!("{points:>6}")),
+                    ])
+                    .fg(Color::DarkGray)
+                }),
+        );
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:<14}", "Score")),
+            Span::raw(format!("{:>6}", score.total())).bold(),
+        ]));
+        lines.push(Line::raw(""));
+
+        lines.extend(self.options.iter().enumerate().map(|(i, option)| {
+            let label = ResultScreen::option_label(*option);
+            if i == self.selected_index {
+                Line::from(vec![
+                    Span::raw("> "),
+                    Span::styled(label, Style::default().fg(Color::Blue)),
+                ])
+            } else {
+                Line::from(vec![Span::raw("  "), Span::raw(label)])
+            }
+        }));
+
+        let height = lines.len() as u16 + 4;
+        let width = lines.iter().map(|l| l.width()).max().unwrap_or(0).max(title.len()) as u16 + 8;
+        let popup_rect = rect.centered(Constraint::Length(width), Constraint::Length(height));
+
+        let block = Block::bordered()
+            .border_set(border::THICK)
+            .title(Line::from(title.bold().fg(color)).centered());
+        let inner_rect = block.inner(popup_rect);
+
+        Clear.render(popup_rect, buf);
+        block.render(popup_rect, buf);
+        Paragraph::new(lines)
+            .alignment(Alignment::Left)
+            .render(inner_rect.inner(Margin::new(3, 1)), buf);
+    }
+}
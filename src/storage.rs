@@ -0,0 +1,52 @@
+pub mod daily;
+pub mod progress;
+pub mod recent;
+pub mod session;
+
+use std::{fs, path::PathBuf};
+
+use color_eyre::eyre::{OptionExt, Result};
+use ron::ser::PrettyConfig;
+use serde::{Serialize, de::DeserializeOwned};
+
+// Per-user data directory, e.g. ~/.local/share/tiles on Linux
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tiles"))
+}
+
+// Returns None if the file is missing, unreadable or corrupt
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let content = fs::read_to_string(data_dir()?.join(file_name)).ok()?;
+    ron::de::from_str(&content).ok()
+}
+
+// Falls back to the default value if the file is missing, unreadable or corrupt
+pub fn load_or_default<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    load(file_name).unwrap_or_default()
+}
+
+// Callers in the game treat saving as best effort: a missing or read-only data directory loses records but
+// should never keep a level from opening, end a game or close the app
+pub fn save<T: Serialize>(file_name: &str, value: &T) -> Result<()> {
+    let dir = data_dir().ok_or_eyre("Could not find a data directory")?;
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join(file_name),
+        ron::ser::to_string_pretty(value, PrettyConfig::new())?,
+    )?;
+    Ok(())
+}
+
+pub fn remove(file_name: &str) -> Result<()> {
+    let path = data_dir()
+        .ok_or_eyre("Could not find a data directory")?
+        .join(file_name);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+pub fn exists(file_name: &str) -> bool {
+    data_dir().is_some_and(|dir| dir.join(file_name).is_file())
+}
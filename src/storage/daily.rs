@@ -0,0 +1,61 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use chrono::{Local, NaiveDate};
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+const FILE_NAME: &str = "daily.ron";
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct DailyRecord {
+    // Only days whose puzzle was cleared
+    days: BTreeMap<NaiveDate, DayResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct DayResult {
+    pub moves: usize,
+    pub time: Duration,
+}
+
+impl DailyRecord {
+    pub fn load() -> Self {
+        super::load_or_default(FILE_NAME)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        super::save(FILE_NAME, self)
+    }
+
+    // The local date decides the puzzle, so players in one time zone share a board
+    pub fn today() -> NaiveDate {
+        Local::now().date_naive()
+    }
+
+    pub fn get(&self, date: NaiveDate) -> Option<DayResult> {
+        self.days.get(&date).copied()
+    }
+
+    // Replays keep the fewest moves and the fastest time independently
+    pub fn record_win(&mut self, date: NaiveDate, moves: usize, time: Duration) {
+        let result = self.days.entry(date).or_insert(DayResult { moves, time });
+        result.moves = result.moves.min(moves);
+        result.time = result.time.min(time);
+    }
+
+    // Consecutive days cleared up to today, or up to yesterday while today's puzzle is still open
+    pub fn streak(&self, today: NaiveDate) -> usize {
+        let mut day = match self.days.contains_key(&today) {
+            true => Some(today),
+            false => today.pred_opt(),
+        };
+        let mut streak = 0;
+        while let Some(date) = day
+            && self.days.contains_key(&date)
+        {
+            streak += 1;
+            day = date.pred_opt();
+        }
+        streak
+    }
+}
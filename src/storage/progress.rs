@@ -0,0 +1,56 @@
+use std::{collections::HashMap, time::Duration};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::game::logic::grid::Grid;
+
+const FILE_NAME: &str = "progress.ron";
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Progress {
+    // Keyed by the hex fingerprint of the level's starting board
+    levels: HashMap<String, LevelRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct LevelRecord {
+    pub completed: bool,
+    pub best_moves: usize,
+    pub best_time: Duration,
+    // Records from before scoring existed read as 0
+    #[serde(default)]
+    pub best_score: usize,
+}
+
+impl Progress {
+    pub fn load() -> Self {
+        super::load_or_default(FILE_NAME)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        super::save(FILE_NAME, self)
+    }
+
+    pub fn get(&self, level: &Grid) -> Option<LevelRecord> {
+        self.levels.get(&Self::key(level)).copied()
+    }
+
+    // Keeps the fewest moves, the fastest time and the highest score independently
+    pub fn record_win(&mut self, level: &Grid, moves: usize, time: Duration, score: usize) {
+        let record = self.levels.entry(Self::key(level)).or_insert(LevelRecord {
+            completed: true,
+            best_moves: moves,
+            best_time: time,
+            best_score: score,
+        });
+        record.completed = true;
+        record.best_moves = record.best_moves.min(moves);
+        record.best_time = record.best_time.min(time);
+        record.best_score = record.best_score.max(score);
+    }
+
+    fn key(level: &Grid) -> String {
+        format!("{:016x}", level.fingerprint())
+    }
+}
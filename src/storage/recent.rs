@@ -0,0 +1,78 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::game::logic::grid::Outcome;
+
+const FILE_NAME: &str = "recent.ron";
+const MAX_ENTRIES: usize = 20;
+
+// Level files opened from the file picker or the command line, most recent first
+#[derive(Serialize, Deserialize, Default)]
+pub struct RecentGames {
+    entries: Vec<RecentEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecentEntry {
+    pub path: PathBuf,
+    pub last_opened: SystemTime,
+    pub last_result: Option<Outcome>,
+}
+
+impl RecentGames {
+    // Drops entries whose file no longer exists
+    pub fn load() -> Self {
+        let mut recent: Self = super::load_or_default(FILE_NAME);
+        recent.entries.retain(|entry| entry.path.is_file());
+        recent
+    }
+
+    pub fn save(&self) -> Result<()> {
+        super::save(FILE_NAME, self)
+    }
+
+    pub fn entries(&self) -> &[RecentEntry] {
+        &self.entries
+    }
+
+    pub fn record_opened(&mut self, path: &Path) {
+        let path = Self::normalize(path);
+        let last_result = self.take(&path).and_then(|entry| entry.last_result);
+        self.entries.insert(
+            0,
+            RecentEntry {
+                path,
+                last_opened: SystemTime::now(),
+                last_result,
+            },
+        );
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn record_result(&mut self, path: &Path, outcome: Outcome) {
+        let path = Self::normalize(path);
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.path == path) {
+            entry.last_result = Some(outcome);
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.take(&Self::normalize(path));
+    }
+
+    fn take(&mut self, path: &Path) -> Option<RecentEntry> {
+        let index = self.entries.iter().position(|entry| entry.path == path)?;
+        Some(self.entries.remove(index))
+    }
+
+    // The same file opened through different relative paths is one entry
+    fn normalize(path: &Path) -> PathBuf {
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+}
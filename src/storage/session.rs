@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::logic::{grid::Grid, history::History, score::Score},
+    screens::game::LevelSource,
+};
+
+const FILE_NAME: &str = "session.ron";
+
+// An unfinished game, saved when leaving GameScreen so it can be resumed later
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    pub grid: Grid,
+    // Board before the first move
+    pub level: Grid,
+    pub source: Option<LevelSource>,
+    pub history: History<(Grid, Score)>,
+    pub score: Score,
+    pub elapsed: Duration,
+}
+
+impl Session {
+    pub fn load() -> Option<Self> {
+        super::load(FILE_NAME)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        super::save(FILE_NAME, self)
+    }
+
+    pub fn clear() -> Result<()> {
+        super::remove(FILE_NAME)
+    }
+
+    pub fn exists() -> bool {
+        super::exists(FILE_NAME)
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+};
+
+// Runs a slow computation, like a solver search or level generation, on its own thread so the UI keeps
+// drawing while it works. The job is handed a flag that is set when the Worker is dropped, and should stop
+// early once it sees it, since nobody is waiting for the result any more.
+pub struct Worker<T> {
+    handle: Option<JoinHandle<T>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> Worker<T> {
+    pub fn spawn(job: impl FnOnce(Arc<AtomicBool>) -> T + Send + 'static) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        Self {
+            handle: Some(thread::spawn(move || job(flag))),
+            cancel,
+        }
+    }
+
+    // Some once the job is done, with Err if it panicked. After that the Worker is spent and returns None.
+    pub fn poll(&mut self) -> Option<Result<T, String>> {
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        let result = self.handle.take()?.join();
+        Some(result.map_err(|panic| {
+            panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "the background job panicked".to_string())
+        }))
+    }
+}
+
+impl<T> Drop for Worker<T> {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}